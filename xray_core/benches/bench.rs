@@ -7,6 +7,8 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use xray_core::buffer::{Buffer, Point};
 use xray_core::buffer_view::BufferView;
+use xray_core::tree::scalars::{Count, Scalar};
+use xray_core::tree::{SeekBias, Tree};
 
 fn add_selection(c: &mut Criterion) {
     c.bench_function("add_selection_below", |b| {
@@ -54,6 +56,53 @@ fn edit(c: &mut Criterion) {
     });
 }
 
+fn tree_extend(c: &mut Criterion) {
+    c.bench_function("tree_extend", |b| {
+        b.iter(|| {
+            let mut tree = Tree::new();
+            tree.extend((0..10_000u32).map(Scalar));
+            tree
+        })
+    });
+    c.bench_function("tree_extend_balanced", |b| {
+        b.iter(|| {
+            let mut tree = Tree::new();
+            tree.extend_balanced((0..10_000u32).map(Scalar));
+            tree
+        })
+    });
+}
+
+fn tree_range_iteration(c: &mut Criterion) {
+    c.bench_function("tree_range_cursor", |b| {
+        let tree = create_scalar_tree(100_000);
+        b.iter(|| {
+            let mut cursor = tree.cursor();
+            cursor.seek(&Count(45_000), SeekBias::Right);
+            let mut sum = 0;
+            while cursor.start::<Count>() < Count(55_000) {
+                sum += cursor.item().unwrap().0;
+                cursor.next();
+            }
+            sum
+        })
+    });
+    c.bench_function("tree_items_in_range", |b| {
+        let tree = create_scalar_tree(100_000);
+        b.iter(|| {
+            tree.items_in_range(&Count(45_000)..&Count(55_000))
+                .map(|item| item.0)
+                .sum::<u32>()
+        })
+    });
+}
+
+fn create_scalar_tree(len: u32) -> Tree<Scalar<u32>> {
+    let mut tree = Tree::new();
+    tree.extend_balanced((0..len).map(Scalar));
+    tree
+}
+
 fn create_buffer_view(lines: usize) -> BufferView {
     let mut buffer = Buffer::new(0);
     for i in 0..lines {
@@ -66,5 +115,11 @@ fn create_buffer_view(lines: usize) -> BufferView {
     BufferView::new(Rc::new(RefCell::new(buffer)), 0, None)
 }
 
-criterion_group!(benches, edit, add_selection);
+criterion_group!(
+    benches,
+    edit,
+    add_selection,
+    tree_extend,
+    tree_range_iteration
+);
 criterion_main!(benches);
@@ -7,6 +7,8 @@ extern crate bytes;
 extern crate lazy_static;
 extern crate futures;
 extern crate parking_lot;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 extern crate seahash;
 extern crate serde;
 #[macro_use]
@@ -14,6 +16,8 @@ extern crate serde_derive;
 #[macro_use]
 extern crate serde_json;
 extern crate smallvec;
+#[cfg(feature = "unicode-segmentation")]
+extern crate unicode_segmentation;
 #[cfg(test)]
 extern crate tokio_core;
 #[cfg(test)]
@@ -32,6 +36,8 @@ pub mod cross_platform;
 pub mod fs;
 pub mod notify_cell;
 pub mod rpc;
+pub mod text;
+pub mod tree;
 pub mod window;
 pub mod workspace;
 
@@ -42,7 +48,6 @@ mod never;
 mod project;
 #[cfg(test)]
 mod stream_ext;
-mod tree;
 
 pub use app::{App, WindowId};
 use futures::future::{Executor, Future};
@@ -0,0 +1,947 @@
+use std::borrow::Cow;
+use std::cmp;
+use std::fmt;
+use std::ops::{Add, AddAssign, Range, Sub};
+use tree::{self, SeekBias, Tree};
+#[cfg(feature = "unicode-segmentation")]
+use unicode_segmentation::UnicodeSegmentation;
+
+#[cfg(not(test))]
+const MAX_CHUNK_BYTES: usize = 128;
+#[cfg(test)]
+const MAX_CHUNK_BYTES: usize = 16;
+// Splitting text evenly yields chunks of at least half the maximum size, minus a few bytes
+// when a split point has to be moved back to a char boundary.
+const MIN_CHUNK_BYTES: usize = MAX_CHUNK_BYTES / 4;
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Text {
+    chunks: Tree<Chunk>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Chunk(String);
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Error {
+    OffsetOutOfRange,
+    NotCharBoundary,
+}
+
+pub struct Lines<'a> {
+    front_cursor: tree::Cursor<'a, Chunk>,
+    front_offset: usize,
+    back_cursor: tree::Cursor<'a, Chunk>,
+    back_offset: usize,
+}
+
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct TextSummary {
+    pub bytes: usize,
+    pub chars: usize,
+    pub lines: Point,
+    #[cfg(feature = "unicode-segmentation")]
+    pub graphemes: usize,
+}
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub struct ByteOffset(pub usize);
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub struct CharOffset(pub usize);
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub struct NewlineCount(pub usize);
+
+/// The number of extended grapheme clusters preceding a position. Chunks are only ever split on
+/// cluster boundaries, so a cluster that would otherwise span a chunk boundary is kept whole and
+/// counted once, by the chunk containing its first byte.
+#[cfg(feature = "unicode-segmentation")]
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub struct GraphemeOffset(pub usize);
+
+/// A position in the text, where `column` is a byte offset into the row.
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Point {
+    pub row: u32,
+    pub column: u32,
+}
+
+impl Text {
+    pub fn new() -> Self {
+        Self {
+            chunks: Tree::new(),
+        }
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        self.chunks.len::<ByteOffset>().0
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.chunks.len::<CharOffset>().0
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn len_graphemes(&self) -> usize {
+        self.chunks.len::<GraphemeOffset>().0
+    }
+
+    /// Returns an iterator over the lines of the text. Like `str::lines`, lines are yielded
+    /// without their line ending (`\n` or `\r\n`) and a trailing line ending does not produce
+    /// an extra empty line. Lines contained in a single chunk are borrowed, while lines
+    /// spanning multiple chunks are copied into an owned string. The iterator can be consumed
+    /// from both ends.
+    pub fn lines(&self) -> Lines {
+        let mut front_cursor = self.chunks.cursor();
+        front_cursor.seek(&NewlineCount(0), SeekBias::Left);
+        let mut back_cursor = self.chunks.cursor();
+        back_cursor.seek(&ByteOffset(self.len_bytes()), SeekBias::Left);
+        Lines {
+            front_cursor,
+            front_offset: 0,
+            back_cursor,
+            back_offset: self.len_bytes(),
+        }
+    }
+
+    /// Converts a byte offset to a char offset. Offsets falling inside a multi-byte character
+    /// are rejected with `Error::NotCharBoundary` rather than snapped to a boundary.
+    pub fn byte_to_char(&self, offset: usize) -> Result<usize, Error> {
+        if offset > self.len_bytes() {
+            return Err(Error::OffsetOutOfRange);
+        }
+
+        let mut cursor = self.chunks.cursor();
+        cursor.seek(&ByteOffset(offset), SeekBias::Left);
+        let mut char_offset = cursor.start::<CharOffset>().0;
+        if let Some(chunk) = cursor.item() {
+            let overshoot = offset - cursor.start::<ByteOffset>().0;
+            let prefix = chunk.0.get(..overshoot).ok_or(Error::NotCharBoundary)?;
+            char_offset += prefix.chars().count();
+        }
+        Ok(char_offset)
+    }
+
+    pub fn char_to_byte(&self, offset: usize) -> Result<usize, Error> {
+        if offset > self.len_chars() {
+            return Err(Error::OffsetOutOfRange);
+        }
+
+        let mut cursor = self.chunks.cursor();
+        cursor.seek(&CharOffset(offset), SeekBias::Left);
+        let mut byte_offset = cursor.start::<ByteOffset>().0;
+        if let Some(chunk) = cursor.item() {
+            let overshoot = offset - cursor.start::<CharOffset>().0;
+            byte_offset += chunk
+                .0
+                .char_indices()
+                .nth(overshoot)
+                .map_or(chunk.0.len(), |(index, _)| index);
+        }
+        Ok(byte_offset)
+    }
+
+    /// Converts a grapheme offset to the byte offset at which that grapheme cluster starts.
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn grapheme_to_byte(&self, offset: usize) -> Result<usize, Error> {
+        if offset > self.len_graphemes() {
+            return Err(Error::OffsetOutOfRange);
+        }
+
+        let mut cursor = self.chunks.cursor();
+        cursor.seek(&GraphemeOffset(offset), SeekBias::Left);
+        let mut byte_offset = cursor.start::<ByteOffset>().0;
+        if let Some(chunk) = cursor.item() {
+            let overshoot = offset - cursor.start::<GraphemeOffset>().0;
+            byte_offset += chunk
+                .0
+                .grapheme_indices(true)
+                .nth(overshoot)
+                .map_or(chunk.0.len(), |(index, _)| index);
+        }
+        Ok(byte_offset)
+    }
+
+    /// Converts a byte offset to a point. Panics if `offset` is out of range.
+    pub fn offset_to_point(&self, offset: usize) -> Point {
+        assert!(offset <= self.len_bytes(), "Offset out of range");
+
+        let mut cursor = self.chunks.cursor();
+        cursor.seek(&ByteOffset(offset), SeekBias::Left);
+        let mut point = cursor.start::<Point>();
+        if let Some(chunk) = cursor.item() {
+            let overshoot = offset - cursor.start::<ByteOffset>().0;
+            point = point + &Point::extent(&chunk.0[..overshoot]);
+        }
+        point
+    }
+
+    /// Converts a point to a byte offset. Points past the end of their row are clamped to the
+    /// end of that row, and points past the last row are clamped to the end of the text.
+    pub fn point_to_offset(&self, point: Point) -> usize {
+        let mut cursor = self.chunks.cursor();
+        cursor.seek(&point, SeekBias::Left);
+        let mut offset = cursor.start::<ByteOffset>().0;
+        if let Some(chunk) = cursor.item() {
+            let mut position = cursor.start::<Point>();
+            for ch in chunk.0.chars() {
+                if position >= point {
+                    break;
+                }
+
+                if ch == '\n' {
+                    if position.row == point.row {
+                        break;
+                    }
+                    position.row += 1;
+                    position.column = 0;
+                } else {
+                    position.column += ch.len_utf8() as u32;
+                }
+                offset += ch.len_utf8();
+            }
+        }
+        offset
+    }
+
+    /// Inserts `text` at the given byte offset. Like `String::insert_str`, this panics if
+    /// `offset` is out of range or does not lie on a char boundary.
+    pub fn insert(&mut self, offset: usize, text: &str) {
+        assert!(offset <= self.len_bytes(), "Offset out of range");
+        self.edit(offset..offset, text);
+    }
+
+    /// Deletes the given byte range. Panics if the range is out of bounds or if either end
+    /// does not lie on a char boundary.
+    pub fn delete(&mut self, range: Range<usize>) {
+        assert!(
+            range.start <= range.end && range.end <= self.len_bytes(),
+            "Range out of bounds"
+        );
+        if range.start < range.end {
+            self.edit(range, "");
+        }
+    }
+
+    /// Replaces the given byte range by re-chunking the chunks it touches. Edits that would
+    /// leave a chunk smaller than `MIN_CHUNK_BYTES` absorb an adjacent chunk, so that all chunks
+    /// stay within `MIN_CHUNK_BYTES..=MAX_CHUNK_BYTES` unless the text fits in a single chunk.
+    fn edit(&mut self, range: Range<usize>, new_text: &str) {
+        let (old_start, old_end, text) = {
+            let mut cursor = self.chunks.cursor();
+            cursor.seek(&ByteOffset(range.start), SeekBias::Left);
+            let mut old_start = cursor.start::<ByteOffset>().0;
+            let prev_chunk = cursor.prev_item();
+            let start_chunk = cursor.item().map_or("", |chunk| chunk.0.as_str());
+            let mut text = start_chunk[..range.start - old_start].to_string();
+            text.push_str(new_text);
+
+            cursor.seek(&ByteOffset(range.end), SeekBias::Left);
+            let mut old_end = cursor.start::<ByteOffset>().0;
+            if let Some(end_chunk) = cursor.item() {
+                text.push_str(&end_chunk.0[range.end - old_end..]);
+                old_end += end_chunk.0.len();
+                cursor.next();
+            }
+
+            if !text.is_empty() && text.len() < MIN_CHUNK_BYTES {
+                if let Some(prev_chunk) = prev_chunk {
+                    text.insert_str(0, &prev_chunk.0);
+                    old_start -= prev_chunk.0.len();
+                } else if let Some(next_chunk) = cursor.item() {
+                    text.push_str(&next_chunk.0);
+                    old_end += next_chunk.0.len();
+                    cursor.next();
+                }
+            }
+
+            // The edited text always keeps the first char of its start chunk, but its end can join
+            // the grapheme cluster starting the next chunk, for example when a zero-width joiner
+            // is inserted before an emoji. Absorb the next chunk in that case, so that no cluster
+            // is split between two chunks.
+            #[cfg(feature = "unicode-segmentation")]
+            {
+                while let Some(next_chunk) = cursor.item() {
+                    if is_grapheme_boundary(&text, &next_chunk.0) {
+                        break;
+                    }
+                    text.push_str(&next_chunk.0);
+                    old_end += next_chunk.0.len();
+                    cursor.next();
+                }
+            }
+
+            (ByteOffset(old_start), ByteOffset(old_end), text)
+        };
+        self.chunks
+            .splice(&old_start..&old_end, Chunk::split(&text));
+    }
+}
+
+impl<'a> From<&'a str> for Text {
+    fn from(text: &'a str) -> Self {
+        let mut result = Self::new();
+        result.chunks.extend(Chunk::split(text));
+        result
+    }
+}
+
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for chunk in self.chunks.iter() {
+            f.write_str(&chunk.0)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = Cow<'a, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front_offset >= self.back_offset {
+            return None;
+        }
+
+        let mut line: Option<Cow<'a, str>> = None;
+        while let Some(chunk) = self.front_cursor.item() {
+            let chunk_start = self.front_cursor.start::<ByteOffset>().0;
+            let remaining = &chunk.0[self.front_offset - chunk_start..];
+            let newline_index = remaining.find('\n');
+            let piece = &remaining[..newline_index.unwrap_or(remaining.len())];
+
+            self.front_offset += piece.len();
+            if newline_index.is_some() {
+                self.front_offset += 1;
+            }
+            if self.front_offset == chunk_start + chunk.0.len() {
+                self.front_cursor.next();
+            }
+
+            line = Some(match line {
+                None => Cow::Borrowed(piece),
+                Some(line) => {
+                    let mut line = line.into_owned();
+                    line.push_str(piece);
+                    Cow::Owned(line)
+                }
+            });
+
+            if newline_index.is_some() {
+                line = line.map(strip_carriage_return);
+                break;
+            }
+        }
+        line
+    }
+}
+
+impl<'a> DoubleEndedIterator for Lines<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.back_offset <= self.front_offset {
+            return None;
+        }
+
+        // The back cursor is always parked on the chunk containing the byte before
+        // `back_offset`, which is either the end of the text or the newline ending the line.
+        let mut line: Option<Cow<'a, str>> = None;
+        let mut end = self.back_offset;
+        let mut has_line_ending = false;
+        while let Some(chunk) = self.back_cursor.item() {
+            let chunk_start = self.back_cursor.start::<ByteOffset>().0;
+            let start = cmp::max(chunk_start, self.front_offset);
+            let mut preceding = &chunk.0[start - chunk_start..end - chunk_start];
+            if end == self.back_offset && preceding.ends_with('\n') {
+                preceding = &preceding[..preceding.len() - 1];
+                has_line_ending = true;
+            }
+
+            let newline_index = preceding.rfind('\n');
+            let piece = &preceding[newline_index.map_or(0, |index| index + 1)..];
+            end = start + preceding.len() - piece.len();
+
+            line = Some(match line {
+                None => Cow::Borrowed(piece),
+                Some(line) => {
+                    let mut prefixed_line = piece.to_string();
+                    prefixed_line.push_str(&line);
+                    Cow::Owned(prefixed_line)
+                }
+            });
+
+            if newline_index.is_some() || start == self.front_offset {
+                break;
+            } else {
+                self.back_cursor.prev();
+            }
+        }
+
+        self.back_offset = end;
+        if has_line_ending {
+            line = line.map(strip_carriage_return);
+        }
+        line
+    }
+}
+
+#[cfg(feature = "unicode-segmentation")]
+fn is_grapheme_boundary(left: &str, right: &str) -> bool {
+    let mut text = String::with_capacity(left.len() + right.len());
+    text.push_str(left);
+    text.push_str(right);
+    text.grapheme_indices(true)
+        .any(|(index, _)| index == left.len())
+}
+
+fn strip_carriage_return(line: Cow<str>) -> Cow<str> {
+    match line {
+        Cow::Borrowed(line) if line.ends_with('\r') => Cow::Borrowed(&line[..line.len() - 1]),
+        Cow::Owned(mut line) => {
+            if line.ends_with('\r') {
+                line.pop();
+            }
+            Cow::Owned(line)
+        }
+        line => line,
+    }
+}
+
+impl Chunk {
+    /// Splits `text` into as few chunks as possible, distributing it evenly between them.
+    fn split(mut text: &str) -> Vec<Chunk> {
+        let mut chunks = Vec::new();
+        while !text.is_empty() {
+            let chunk_count = (text.len() + MAX_CHUNK_BYTES - 1) / MAX_CHUNK_BYTES;
+            let split_index = split_boundary(text, (text.len() + chunk_count - 1) / chunk_count);
+            let (chunk, rest) = text.split_at(split_index);
+            chunks.push(Chunk(chunk.to_string()));
+            text = rest;
+        }
+        chunks
+    }
+}
+
+/// Moves `index` back to the nearest char boundary.
+#[cfg(not(feature = "unicode-segmentation"))]
+fn split_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Moves `index` back to the nearest grapheme cluster boundary, or forward past the first cluster
+/// if it is longer than `index`. A chunk can thus exceed `MAX_CHUNK_BYTES` when it consists of a
+/// single oversized cluster.
+#[cfg(feature = "unicode-segmentation")]
+fn split_boundary(text: &str, index: usize) -> usize {
+    let mut boundaries = text
+        .grapheme_indices(true)
+        .skip(1)
+        .map(|(boundary, _)| boundary)
+        .chain(Some(text.len()));
+    let mut split_index = boundaries.next().unwrap();
+    for boundary in boundaries {
+        if boundary > index {
+            break;
+        }
+        split_index = boundary;
+    }
+    split_index
+}
+
+impl tree::Item for Chunk {
+    type Summary = TextSummary;
+
+    fn summarize(&self) -> Self::Summary {
+        TextSummary {
+            bytes: self.0.len(),
+            chars: self.0.chars().count(),
+            lines: Point::extent(&self.0),
+            #[cfg(feature = "unicode-segmentation")]
+            graphemes: self.0.graphemes(true).count(),
+        }
+    }
+}
+
+impl<'a> AddAssign<&'a Self> for TextSummary {
+    fn add_assign(&mut self, other: &Self) {
+        self.bytes += other.bytes;
+        self.chars += other.chars;
+        self.lines = self.lines + &other.lines;
+        #[cfg(feature = "unicode-segmentation")]
+        {
+            self.graphemes += other.graphemes;
+        }
+    }
+}
+
+impl tree::Dimension for ByteOffset {
+    type Summary = TextSummary;
+
+    fn from_summary(summary: &Self::Summary) -> Self {
+        ByteOffset(summary.bytes)
+    }
+}
+
+impl<'a> Add<&'a Self> for ByteOffset {
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self {
+        ByteOffset(self.0 + other.0)
+    }
+}
+
+impl<'a> Sub<&'a Self> for ByteOffset {
+    type Output = Self;
+
+    fn sub(self, other: &Self) -> Self {
+        ByteOffset(self.0 - other.0)
+    }
+}
+
+impl tree::Dimension for CharOffset {
+    type Summary = TextSummary;
+
+    fn from_summary(summary: &Self::Summary) -> Self {
+        CharOffset(summary.chars)
+    }
+}
+
+impl<'a> Add<&'a Self> for CharOffset {
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self {
+        CharOffset(self.0 + other.0)
+    }
+}
+
+#[cfg(feature = "unicode-segmentation")]
+impl tree::Dimension for GraphemeOffset {
+    type Summary = TextSummary;
+
+    fn from_summary(summary: &Self::Summary) -> Self {
+        GraphemeOffset(summary.graphemes)
+    }
+}
+
+#[cfg(feature = "unicode-segmentation")]
+impl<'a> Add<&'a Self> for GraphemeOffset {
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self {
+        GraphemeOffset(self.0 + other.0)
+    }
+}
+
+impl tree::Dimension for NewlineCount {
+    type Summary = TextSummary;
+
+    fn from_summary(summary: &Self::Summary) -> Self {
+        NewlineCount(summary.lines.row as usize)
+    }
+}
+
+impl<'a> Add<&'a Self> for NewlineCount {
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self {
+        NewlineCount(self.0 + other.0)
+    }
+}
+
+impl Point {
+    pub fn new(row: u32, column: u32) -> Self {
+        Point { row, column }
+    }
+
+    fn extent(text: &str) -> Self {
+        let mut point = Point::default();
+        for byte in text.bytes() {
+            if byte == b'\n' {
+                point.row += 1;
+                point.column = 0;
+            } else {
+                point.column += 1;
+            }
+        }
+        point
+    }
+}
+
+impl tree::Dimension for Point {
+    type Summary = TextSummary;
+
+    fn from_summary(summary: &Self::Summary) -> Self {
+        summary.lines
+    }
+}
+
+impl<'a> Add<&'a Self> for Point {
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self {
+        if other.row == 0 {
+            Point::new(self.row, self.column + other.column)
+        } else {
+            Point::new(self.row + other.row, other.column)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use super::*;
+
+    #[test]
+    fn test_edits_across_chunk_boundaries() {
+        let mut text = Text::from("abcdefghijklmnopqrstuvwxyz");
+        assert_eq!(text.len_bytes(), 26);
+        assert_eq!(text.to_string(), "abcdefghijklmnopqrstuvwxyz");
+
+        text.insert(8, "123");
+        assert_eq!(text.to_string(), "abcdefgh123ijklmnopqrstuvwxyz");
+
+        text.delete(5..20);
+        assert_eq!(text.to_string(), "abcderstuvwxyz");
+
+        text.insert(0, "αβγ\nδ");
+        assert_eq!(text.to_string(), "αβγ\nδabcderstuvwxyz");
+        assert_eq!(text.len_bytes(), 23);
+        assert_eq!(text.len_chars(), 19);
+        assert_eq!(text.chunks.len::<NewlineCount>(), NewlineCount(1));
+
+        let len = text.len_bytes();
+        text.delete(0..len);
+        assert_eq!(text.to_string(), "");
+        assert_eq!(text.len_chars(), 0);
+    }
+
+    #[test]
+    fn test_point_conversions() {
+        for line_ending in &["\n", "\r\n"] {
+            let lines = ["abc", "", "défg", "日本語のテキスト", "", "xyz"];
+            let string = lines.join(line_ending);
+            let text = Text::from(string.as_str());
+
+            for (offset, _) in string.char_indices().chain(Some((string.len(), ' '))) {
+                let point = text.offset_to_point(offset);
+                let prefix = &string[..offset];
+                assert_eq!(point.row as usize, prefix.matches('\n').count());
+                assert_eq!(
+                    point.column as usize,
+                    prefix.len() - prefix.rfind('\n').map_or(0, |index| index + 1)
+                );
+                assert_eq!(text.point_to_offset(point), offset);
+            }
+
+            let last_row = lines.len() as u32 - 1;
+            assert_eq!(text.offset_to_point(string.len()), Point::new(last_row, 3));
+            assert_eq!(
+                text.point_to_offset(Point::new(0, 100)),
+                string.find('\n').unwrap()
+            );
+            assert_eq!(text.point_to_offset(Point::new(last_row, 4)), string.len());
+            assert_eq!(
+                text.point_to_offset(Point::new(last_row + 1, 0)),
+                string.len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_offset_conversions() {
+        let string = "abc日本語defαβγ🎉🎉xyz";
+        let text = Text::from(string);
+
+        let mut char_offset = 0;
+        for (byte_offset, ch) in string.char_indices() {
+            assert_eq!(text.byte_to_char(byte_offset), Ok(char_offset));
+            assert_eq!(text.char_to_byte(char_offset), Ok(byte_offset));
+            for inner_offset in byte_offset + 1..byte_offset + ch.len_utf8() {
+                assert_eq!(text.byte_to_char(inner_offset), Err(Error::NotCharBoundary));
+            }
+            char_offset += 1;
+        }
+
+        assert_eq!(text.byte_to_char(string.len()), Ok(char_offset));
+        assert_eq!(text.char_to_byte(char_offset), Ok(string.len()));
+        assert_eq!(
+            text.byte_to_char(string.len() + 1),
+            Err(Error::OffsetOutOfRange)
+        );
+        assert_eq!(
+            text.char_to_byte(char_offset + 1),
+            Err(Error::OffsetOutOfRange)
+        );
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn test_graphemes() {
+        // A family emoji is a 25-byte cluster of four people joined by zero-width joiners.
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}";
+        let string = format!("abcdefghijk{}lmn{}e\u{301}xyz", family, family);
+        let text = Text::from(string.as_str());
+        assert_eq!(text.to_string(), string);
+        assert_eq!(text.len_graphemes(), 20);
+        assert_chunks_on_grapheme_boundaries(&text);
+
+        let expected_offsets = string.grapheme_indices(true).map(|(index, _)| index);
+        for (grapheme_offset, byte_offset) in expected_offsets.enumerate() {
+            assert_eq!(text.grapheme_to_byte(grapheme_offset), Ok(byte_offset));
+        }
+        assert_eq!(text.grapheme_to_byte(20), Ok(string.len()));
+        assert_eq!(text.grapheme_to_byte(21), Err(Error::OffsetOutOfRange));
+
+        // Inserting a joiner at the end of a chunk merges its last cluster with the next chunk's
+        // first cluster.
+        let mut text = Text::new();
+        text.chunks.extend(vec![
+            Chunk("abcdefghijklmno\u{1F468}".to_string()),
+            Chunk("\u{1F469}rstuvwxyz".to_string()),
+        ]);
+        text.insert(19, "\u{200D}");
+        assert_eq!(text.len_graphemes(), 25);
+        assert_chunks_on_grapheme_boundaries(&text);
+
+        // So does deleting everything between a joiner and an emoji in another chunk.
+        let mut text = Text::new();
+        text.chunks.extend(vec![
+            Chunk("abcdefghijklmn\u{1F468}\u{200D}".to_string()),
+            Chunk("0123456789abcdef".to_string()),
+            Chunk("\u{1F469}xyz".to_string()),
+        ]);
+        text.delete(21..37);
+        assert_eq!(
+            text.to_string(),
+            "abcdefghijklmn\u{1F468}\u{200D}\u{1F469}xyz"
+        );
+        assert_eq!(text.len_graphemes(), 18);
+        assert_chunks_on_grapheme_boundaries(&text);
+    }
+
+    #[test]
+    fn test_lines() {
+        for string in &[
+            "",
+            "\n",
+            "\n\n",
+            "abc",
+            "abc\n",
+            "abc\ndefghijklmnopqrstuvwxyz\n\nxyz",
+            "a long first line\r\nsecond\r\n\r\nthird one\r\n",
+            "αβγ\nδεζηθικλμν\n日本語",
+        ] {
+            let text = Text::from(*string);
+            let expected_lines = string.lines().collect::<Vec<_>>();
+            assert_eq!(text.lines().collect::<Vec<_>>(), expected_lines);
+
+            let mut reversed_lines = text.lines().rev().collect::<Vec<_>>();
+            reversed_lines.reverse();
+            assert_eq!(reversed_lines, expected_lines);
+
+            for split in 0..expected_lines.len() + 1 {
+                let mut lines = text.lines();
+                let mut front_lines = lines.by_ref().take(split).collect::<Vec<_>>();
+                let mut back_lines = lines.rev().collect::<Vec<_>>();
+                back_lines.reverse();
+                front_lines.extend(back_lines);
+                assert_eq!(front_lines, expected_lines);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_edits() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        for seed in 0..100 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let mut reference = String::new();
+            let mut text = Text::new();
+
+            for _ in 0..50 {
+                let start = random_char_boundary(&mut rng, &reference);
+                if rng.gen() {
+                    let new_text = random_string(&mut rng);
+                    text.insert(start, &new_text);
+                    reference.insert_str(start, &new_text);
+                } else {
+                    let end = start + random_char_boundary(&mut rng, &reference[start..]);
+                    text.delete(start..end);
+                    reference.drain(start..end);
+                }
+
+                assert_eq!(text.to_string(), reference);
+                assert_eq!(text.len_bytes(), reference.len());
+                assert_eq!(text.len_chars(), reference.chars().count());
+                #[cfg(feature = "unicode-segmentation")]
+                assert_eq!(text.len_graphemes(), reference.graphemes(true).count());
+            }
+        }
+    }
+
+    #[test]
+    fn test_diff_by_byte_offset() {
+        let text = Text::from("abcdefghijklmnopqrstuvwxyz".repeat(10).as_str());
+        let mut edited_text = text.clone();
+        edited_text.insert(130, "123");
+        edited_text.delete(140..150);
+        let edits = text.chunks.diff::<ByteOffset>(&edited_text.chunks);
+
+        // Only the chunks around the edit are replaced.
+        let old_chunks = text.chunks.iter().collect::<Vec<_>>();
+        let new_chunks = edited_text.chunks.iter().collect::<Vec<_>>();
+        let prefix_count = old_chunks
+            .iter()
+            .zip(&new_chunks)
+            .take_while(|&(old, new)| old == new)
+            .count();
+        let suffix_count = old_chunks
+            .iter()
+            .rev()
+            .zip(new_chunks.iter().rev())
+            .take_while(|&(old, new)| old == new)
+            .count();
+        let start: usize = old_chunks[..prefix_count].iter().map(|c| c.0.len()).sum();
+        let end: usize = old_chunks[..old_chunks.len() - suffix_count]
+            .iter()
+            .map(|c| c.0.len())
+            .sum();
+        assert!(start <= 130 && end >= 150);
+        assert!(end - start < 20 + 2 * MAX_CHUNK_BYTES);
+        assert_eq!(
+            edits,
+            vec![
+                tree::Edit::Insert {
+                    position: ByteOffset(start),
+                    items: new_chunks[prefix_count..new_chunks.len() - suffix_count]
+                        .iter()
+                        .map(|&chunk| chunk.clone())
+                        .collect(),
+                },
+                tree::Edit::Remove {
+                    range: ByteOffset(start)..ByteOffset(end),
+                },
+            ]
+        );
+
+        let mut patched_chunks = text.chunks.clone();
+        patched_chunks.apply_edits(&edits);
+        assert_eq!(
+            Text {
+                chunks: patched_chunks
+            }
+            .to_string(),
+            edited_text.to_string()
+        );
+    }
+
+    #[test]
+    fn test_splice_reporting() {
+        let mut text = Text::from("one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\n");
+        let chunks = text.chunks.iter().cloned().collect::<Vec<_>>();
+        let start = chunks[0].0.len();
+        let end = start + chunks[1].0.len();
+        let newlines = |s: &str| s.matches('\n').count();
+
+        let replacement = Chunk("x\ny\nz\n".to_string());
+        let edit = text.chunks.clone().splice_reporting::<_, ByteOffset, _>(
+            &ByteOffset(start)..&ByteOffset(end),
+            Some(replacement.clone()),
+        );
+        assert_eq!(
+            edit,
+            tree::EditSummary {
+                start: ByteOffset(start),
+                old_len: ByteOffset(chunks[1].0.len()),
+                new_len: ByteOffset(6),
+            }
+        );
+
+        let edit = text.chunks.splice_reporting::<_, NewlineCount, _>(
+            &ByteOffset(start)..&ByteOffset(end),
+            Some(replacement),
+        );
+        assert_eq!(
+            edit,
+            tree::EditSummary {
+                start: NewlineCount(newlines(&chunks[0].0)),
+                old_len: NewlineCount(newlines(&chunks[1].0)),
+                new_len: NewlineCount(3),
+            }
+        );
+        let mut expected_text = chunks[0].0.clone();
+        expected_text.push_str("x\ny\nz\n");
+        expected_text.extend(chunks[2..].iter().map(|chunk| chunk.0.as_str()));
+        assert_eq!(text.to_string(), expected_text);
+    }
+
+    #[test]
+    fn test_chunk_sizes_after_random_edits() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        let mut rng = StdRng::from_seed(&[1]);
+        let mut reference = String::new();
+        let mut text = Text::new();
+        for _ in 0..5000 {
+            let start = random_char_boundary(&mut rng, &reference);
+            if reference.len() < 200 || rng.gen_weighted_bool(3) {
+                let new_text = random_string(&mut rng);
+                text.insert(start, &new_text);
+                reference.insert_str(start, &new_text);
+            } else {
+                let end = start + random_char_boundary(&mut rng, &reference[start..]);
+                text.delete(start..end);
+                reference.drain(start..end);
+            }
+
+            let chunks = text.chunks.iter().collect::<Vec<_>>();
+            for chunk in &chunks {
+                assert!(chunk.0.len() <= MAX_CHUNK_BYTES);
+                if chunks.len() > 1 {
+                    assert!(chunk.0.len() >= MIN_CHUNK_BYTES);
+                }
+            }
+        }
+
+        assert_eq!(text.to_string(), reference);
+        let chunk_count = text.chunks.iter().count();
+        let average_chunk_size = text.len_bytes() / chunk_count;
+        assert!(average_chunk_size >= MIN_CHUNK_BYTES && average_chunk_size <= MAX_CHUNK_BYTES);
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    fn assert_chunks_on_grapheme_boundaries(text: &Text) {
+        let string = text.to_string();
+        let mut offset = 0;
+        for chunk in text.chunks.iter() {
+            offset += chunk.0.len();
+            assert!(
+                string
+                    .grapheme_indices(true)
+                    .any(|(index, _)| index == offset)
+                    || offset == string.len()
+            );
+        }
+    }
+
+    fn random_char_boundary<R: self::rand::Rng>(rng: &mut R, text: &str) -> usize {
+        let mut offset = rng.gen_range(0, text.len() + 1);
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+
+    fn random_string<R: self::rand::Rng>(rng: &mut R) -> String {
+        let len = rng.gen_range(0, 20);
+        (0..len)
+            .map(|_| *rng.choose(&['a', 'b', '\n', 'é', '日', '🎉']).unwrap())
+            .collect()
+    }
+}
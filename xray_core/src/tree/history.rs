@@ -0,0 +1,133 @@
+use super::{Item, Tree};
+use std::collections::VecDeque;
+
+/// Keeps a bounded sequence of versions of a tree for undo and redo. Cloning a tree is O(1) and
+/// consecutive versions share all nodes an edit didn't touch, so retaining many versions of a
+/// large tree only costs memory proportional to the size of the edits between them.
+pub struct History<T: Item> {
+    versions: VecDeque<Tree<T>>,
+    current: usize,
+    max_versions: usize,
+}
+
+impl<T: Item> History<T> {
+    /// Creates an empty history retaining at most `max_versions` versions. Panics if
+    /// `max_versions` is zero.
+    pub fn new(max_versions: usize) -> Self {
+        assert!(max_versions > 0, "History must retain at least one version");
+        History {
+            versions: VecDeque::new(),
+            current: 0,
+            max_versions,
+        }
+    }
+
+    /// Records `tree` as the newest version. Versions that were undone are discarded, and the
+    /// oldest version is dropped once more than `max_versions` are retained.
+    pub fn push_version(&mut self, tree: Tree<T>) {
+        if !self.versions.is_empty() {
+            self.versions.truncate(self.current + 1);
+        }
+        self.versions.push_back(tree);
+        if self.versions.len() > self.max_versions {
+            self.versions.pop_front();
+        }
+        self.current = self.versions.len() - 1;
+    }
+
+    pub fn current(&self) -> Option<&Tree<T>> {
+        self.versions.get(self.current)
+    }
+
+    /// Steps back to the previous version and returns it, or returns `None` if there is no older
+    /// version left.
+    pub fn undo(&mut self) -> Option<Tree<T>> {
+        if self.current == 0 {
+            None
+        } else {
+            self.current -= 1;
+            self.current().cloned()
+        }
+    }
+
+    /// Steps forward to the version that was last undone and returns it, or returns `None` if no
+    /// version has been undone since the last push.
+    pub fn redo(&mut self) -> Option<Tree<T>> {
+        if self.current + 1 < self.versions.len() {
+            self.current += 1;
+            self.current().cloned()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::scalars::{Count, Scalar};
+    use super::*;
+    use std::sync::Arc;
+
+    fn items(tree: Option<Tree<Scalar<u16>>>) -> Option<Vec<u16>> {
+        tree.map(|tree| tree.iter().map(|item| item.0).collect())
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = History::new(10);
+        assert!(history.current().is_none());
+        assert_eq!(items(history.undo()), None);
+        assert_eq!(items(history.redo()), None);
+
+        let mut tree = Tree::new();
+        for i in 0..5 {
+            tree.push(Scalar(i));
+            history.push_version(tree.clone());
+        }
+        assert_eq!(items(history.undo()), Some(vec![0, 1, 2, 3]));
+        assert_eq!(items(history.undo()), Some(vec![0, 1, 2]));
+        assert_eq!(items(history.redo()), Some(vec![0, 1, 2, 3]));
+        assert_eq!(items(history.redo()), Some(vec![0, 1, 2, 3, 4]));
+        assert_eq!(items(history.redo()), None);
+
+        // Pushing after an undo discards the undone versions.
+        history.undo();
+        history.undo();
+        let mut tree = history.current().unwrap().clone();
+        tree.push(Scalar(10));
+        history.push_version(tree);
+        assert_eq!(items(history.redo()), None);
+        assert_eq!(items(history.undo()), Some(vec![0, 1, 2]));
+        assert_eq!(items(history.redo()), Some(vec![0, 1, 2, 10]));
+    }
+
+    #[test]
+    fn test_bounded_versions() {
+        let mut history = History::new(3);
+        let mut tree = Tree::new();
+        for i in 0..10 {
+            tree.push(Scalar(i));
+            history.push_version(tree.clone());
+        }
+        assert_eq!(items(history.undo()), Some((0..9).collect()));
+        assert_eq!(items(history.undo()), Some((0..8).collect()));
+        assert_eq!(items(history.undo()), None);
+    }
+
+    #[test]
+    fn test_dropping_versions_keeps_shared_nodes() {
+        let mut history = History::new(1);
+        let mut tree = Tree::new();
+        tree.extend((0..1000u16).map(Scalar));
+        let root = Arc::downgrade(&tree.0);
+        let first_leaf = Arc::downgrade(&tree.leaf_trees().next().unwrap().0);
+        let last_leaf = Arc::downgrade(&tree.leaf_trees().last().unwrap().0);
+        history.push_version(tree.clone());
+
+        tree.splice(&Count(999)..&Count(1000), Some(Scalar(2000)));
+        history.push_version(tree);
+        assert!(root.upgrade().is_none());
+        assert!(last_leaf.upgrade().is_none());
+        assert!(first_leaf.upgrade().is_some());
+    }
+}
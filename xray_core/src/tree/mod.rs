@@ -0,0 +1,5680 @@
+pub mod history;
+pub mod scalars;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use serde::de::{SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::clone::Clone;
+use std::cmp;
+use std::cmp::Ordering;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Add, AddAssign, Range, Sub};
+use std::sync::Arc;
+
+const MIN_CHILDREN: usize = 2;
+const MAX_CHILDREN: usize = 4;
+/// Nodes below this height are summarized on the current thread by `Tree::par_range_summary`,
+/// since splitting them up would cost more than summarizing their few items.
+#[cfg(feature = "rayon")]
+const PARALLEL_MIN_HEIGHT: u16 = 3;
+
+pub trait Item: Clone + Eq + fmt::Debug {
+    type Summary: for<'a> AddAssign<&'a Self::Summary> + Default + Eq + Clone + fmt::Debug;
+
+    fn summarize(&self) -> Self::Summary;
+
+    /// Adds `other` to `summary` like `AddAssign`, but allows implementors to report that the
+    /// result can't be represented. Fallible bulk operations such as `Tree::try_extend_balanced`
+    /// combine summaries through this hook instead of producing a tree with corrupt summaries.
+    fn checked_add_summary(
+        summary: &mut Self::Summary,
+        other: &Self::Summary,
+    ) -> Result<(), SummaryOverflow> {
+        *summary += other;
+        Ok(())
+    }
+}
+
+/// Implemented by summaries that track the maximum of some key over the items they summarize.
+/// Since every subtree's summary knows its own maximum, the item holding the maximum of the
+/// whole tree can be found by `Tree::max_by_key_summary` without visiting the other items.
+pub trait MaxSummary {
+    type Key: Ord;
+
+    fn max_key(&self) -> Self::Key;
+}
+
+pub trait Dimension: for<'a> Add<&'a Self, Output = Self> + Ord + Clone + fmt::Debug {
+    type Summary: Default + Eq + Clone + fmt::Debug;
+
+    fn from_summary(summary: &Self::Summary) -> Self;
+
+    fn default() -> Self {
+        Self::from_summary(&Self::Summary::default())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Tree<T: Item>(Arc<Node<T>>);
+
+/// The fanout of a tree's internal nodes. Every internal node carries the config of the tree it
+/// was created for, and nodes created while pushing, splitting or slicing inherit it from the
+/// tree being modified.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct TreeConfig {
+    pub min_children: usize,
+    pub max_children: usize,
+}
+
+pub struct TreeBuilder<T: Item> {
+    config: TreeConfig,
+    item: PhantomData<T>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Node<T: Item> {
+    Internal {
+        rightmost_leaf: Option<Tree<T>>,
+        summary: T::Summary,
+        children: Vec<Tree<T>>,
+        height: u16,
+        config: TreeConfig,
+    },
+    Leaf {
+        summary: T::Summary,
+        value: T,
+    },
+}
+
+pub struct Iter<'a, T: 'a + Item> {
+    tree: &'a Tree<T>,
+    did_start: bool,
+    stack: Vec<(&'a Tree<T>, usize)>,
+    peeked: Option<Option<&'a T>>,
+}
+
+pub struct RevIter<'a, T: 'a + Item> {
+    stack: Vec<&'a Tree<T>>,
+}
+
+pub struct SubtreesAtHeight<'a, T: 'a + Item> {
+    height: u16,
+    stack: Vec<&'a Tree<T>>,
+}
+
+pub struct Runs<'a, T: 'a + Item> {
+    iter: Peekable<Iter<'a, T>>,
+}
+
+pub struct RangeItems<'a, T: 'a + Item, D> {
+    stack: Vec<(&'a Tree<T>, usize)>,
+    position: D,
+    end: D,
+}
+
+pub struct RChunksBy<'a, T: 'a + Item, D> {
+    cursor: Cursor<'a, T>,
+    budget: D,
+    end: Option<D>,
+}
+
+pub struct WindowsSummaries<'a, T: 'a + Item, D> {
+    start_cursor: Cursor<'a, T>,
+    end_cursor: Cursor<'a, T>,
+    width: D,
+    len: D,
+    window: VecDeque<T::Summary>,
+}
+
+struct TreeVisitor<T>(PhantomData<T>);
+
+/// A position that cursors can seek to, compared against the end of each subtree they pass.
+trait SeekTarget<S> {
+    /// Compares the target with the position `extent` past `start`.
+    fn cmp_end(&self, start: &S, extent: &S) -> Ordering;
+}
+
+struct SeekByComparator<F>(F);
+
+/// Collects the subtrees a cursor moves past while seeking.
+trait SeekAggregate<T: Item> {
+    fn push_subtree(&mut self, subtree: &Tree<T>);
+}
+
+struct SummarySeekAggregate<S>(S);
+
+#[derive(Debug)]
+pub struct Cursor<'a, T: 'a + Item> {
+    tree: &'a Tree<T>,
+    did_seek: bool,
+    stack: Vec<(&'a Tree<T>, usize, T::Summary)>,
+    prev_leaf: Option<&'a Tree<T>>,
+    summary: T::Summary,
+}
+
+/// The prefix returned by `Cursor::slice_with_boundary`, along with whether the slice ended on a
+/// boundary between items rather than inside an item.
+#[derive(Debug)]
+pub struct Slice<T: Item> {
+    pub tree: Tree<T>,
+    pub ended_exactly: bool,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum SeekBias {
+    Left,
+    Right,
+}
+
+/// Tells `Tree::fold_items` whether to keep folding or to stop with a final value.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ControlFlow<B, C> {
+    Continue(C),
+    Break(B),
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum SpliceError {
+    RangeReversed,
+    RangeOutOfBounds,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct SummaryOverflow;
+
+/// Returned by `Tree::try_from_sorted_iter` with the index of the first item whose key is smaller
+/// than the key of the item before it.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct UnsortedError {
+    pub index: usize,
+}
+
+/// Returned by `Tree::try_get` with the requested position and the length of the tree, both
+/// measured in the dimension the position was given in.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct OutOfRange<D> {
+    pub position: D,
+    pub len: D,
+}
+
+/// Describes the structure of a tree without its items, so that tests can compare the exact shape
+/// a sequence of operations produces.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ShapeNode {
+    Internal {
+        height: u16,
+        children: Vec<ShapeNode>,
+    },
+    Leaf,
+}
+
+/// A change to a tree, with positions expressed in dimension `D` of the tree before any of the
+/// edits in a batch are applied.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Edit<D, T> {
+    Insert { position: D, items: Vec<T> },
+    Remove { range: Range<D> },
+}
+
+/// Describes where a splice happened and how long the replaced and inserted items are, measured
+/// in a dimension that may differ from the one the splice range was given in.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct EditSummary<D> {
+    pub start: D,
+    pub old_len: D,
+    pub new_len: D,
+}
+
+/// Trees are equal if they contain equal items, regardless of how the items are grouped into
+/// nodes. Subtrees shared by both trees are skipped without comparing their items, so comparing
+/// a tree with an edited clone of itself only visits the edited parts.
+impl<T: Item> PartialEq for Tree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_by(other, |a, b| a == b)
+    }
+}
+
+impl<T: Item> Eq for Tree<T> {}
+
+impl<T: Item> PartialEq<[T]> for Tree<T> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Item> PartialEq<Vec<T>> for Tree<T> {
+    fn eq(&self, other: &Vec<T>) -> bool {
+        *self == other[..]
+    }
+}
+
+impl<T: Item> Extend<T> for Tree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        for item in items.into_iter() {
+            self.push(item);
+        }
+    }
+}
+
+impl<'a, T: 'a + Item + Copy> Extend<&'a T> for Tree<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, items: I) {
+        self.extend(items.into_iter().cloned());
+    }
+}
+
+/// Trees are serialized as a sequence of their items. Counting the items requires an extra pass
+/// over the tree, since serializers like bincode need to know the length up front.
+impl<T: Item + Serialize> Serialize for Tree<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.iter().count()))?;
+        for item in self.iter() {
+            seq.serialize_element(item)?;
+        }
+        seq.end()
+    }
+}
+
+/// Items are pushed onto the tree as they are deserialized rather than being collected first,
+/// so deserializing never holds more than the tree itself in memory.
+impl<'de, T: Item + Deserialize<'de>> Deserialize<'de> for Tree<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(TreeVisitor(PhantomData))
+    }
+}
+
+impl<'de, T: Item + Deserialize<'de>> Visitor<'de> for TreeVisitor<T> {
+    type Value = Tree<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of tree items")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut tree = Tree::new();
+        while let Some(item) = seq.next_element()? {
+            tree.push(item);
+        }
+        Ok(tree)
+    }
+}
+
+impl<'a, T: Item> Tree<T> {
+    pub fn new() -> Self {
+        Self::with_config(TreeConfig::default())
+    }
+
+    /// Returns a builder for an empty tree with a custom fanout.
+    pub fn builder() -> TreeBuilder<T> {
+        TreeBuilder {
+            config: TreeConfig::default(),
+            item: PhantomData,
+        }
+    }
+
+    fn with_config(config: TreeConfig) -> Self {
+        Self::from_children(vec![], config)
+    }
+
+    pub fn from_item(item: T) -> Self {
+        let mut tree = Self::new();
+        tree.push(item);
+        tree
+    }
+
+    fn from_children(children: Vec<Self>, config: TreeConfig) -> Self {
+        let summary = Self::summarize_children(&children);
+        let rightmost_leaf = children
+            .last()
+            .and_then(|last_child| last_child.rightmost_leaf().cloned());
+        let height = children.get(0).map(|c| c.height()).unwrap_or(0) + 1;
+
+        Tree(Arc::new(Node::Internal {
+            rightmost_leaf,
+            summary,
+            children,
+            height,
+            config,
+        }))
+    }
+
+    fn try_from_children(children: Vec<Self>, config: TreeConfig) -> Result<Self, SummaryOverflow> {
+        let mut summary = T::Summary::default();
+        for child in &children {
+            T::checked_add_summary(&mut summary, child.summary())?;
+        }
+        let rightmost_leaf = children
+            .last()
+            .and_then(|last_child| last_child.rightmost_leaf().cloned());
+        let height = children.get(0).map(|c| c.height()).unwrap_or(0) + 1;
+
+        Ok(Tree(Arc::new(Node::Internal {
+            rightmost_leaf,
+            summary,
+            children,
+            height,
+            config,
+        })))
+    }
+
+    fn summarize_children(children: &[Tree<T>]) -> T::Summary {
+        Self::combined_summary(children)
+    }
+
+    /// Folds the summaries of the given trees in iteration order, yielding the summary their
+    /// concatenation would have. For summaries whose combination isn't commutative, the result
+    /// depends on this order.
+    pub fn combined_summary<'b, I>(trees: I) -> T::Summary
+    where
+        I: IntoIterator<Item = &'b Tree<T>>,
+        T: 'b,
+    {
+        let mut summary = T::Summary::default();
+        for tree in trees {
+            summary += tree.summary();
+        }
+        summary
+    }
+
+    pub fn iter(&self) -> Iter<T> {
+        Iter::new(self)
+    }
+
+    /// Replaces the contents of `out` with clones of the tree's items. `out` keeps its capacity,
+    /// so reusing the same vector for trees of similar length avoids reallocating it.
+    pub fn collect_into(&self, out: &mut Vec<T>) {
+        out.clear();
+        out.extend(self.iter().cloned());
+    }
+
+    /// Yields the items from last to first. Unlike `Iter`, this isn't a double-ended iterator:
+    /// it only descends into the rightmost unvisited child of each node, keeping the unvisited
+    /// siblings on a stack, so it can't be combined with forward iteration.
+    pub fn rev_iter(&self) -> RevIter<T> {
+        RevIter { stack: vec![self] }
+    }
+
+    /// Yields each item along with the summary of all items up to and including it. This is a
+    /// single O(n) pass over the leaves' cached summaries, so no item is summarized again, but
+    /// every yielded summary is a fresh clone of the running total.
+    pub fn iter_summaries(&self) -> impl Iterator<Item = (&T, T::Summary)> {
+        self.leaf_trees()
+            .scan(T::Summary::default(), |summary, leaf| {
+                *summary += leaf.summary();
+                Some((leaf.value(), summary.clone()))
+            })
+    }
+
+    /// Passes items to `f` along with the summary of all items up to and including them, until
+    /// `f` returns false. Returns the summary of the items before the one `f` rejected, or of the
+    /// whole tree if `f` accepted every item. `f` must be monotone, i.e. once it rejects an item
+    /// it must reject all later ones. This allows a subtree to be skipped using its cached
+    /// summary when `f` accepts its last item, so only the path to the rejected item is visited.
+    pub fn iter_summaries_while<F>(&self, mut f: F) -> T::Summary
+    where
+        F: FnMut(&T, &T::Summary) -> bool,
+    {
+        let mut summary = T::Summary::default();
+        self.iter_summaries_while_recursive(&mut summary, &mut f);
+        summary
+    }
+
+    fn iter_summaries_while_recursive<F>(&self, summary: &mut T::Summary, f: &mut F) -> bool
+    where
+        F: FnMut(&T, &T::Summary) -> bool,
+    {
+        for child in self.children() {
+            let mut child_end = summary.clone();
+            child_end += child.summary();
+            if child.height() == 0 {
+                if !f(child.value(), &child_end) {
+                    return false;
+                }
+            } else if !f(child.last().unwrap(), &child_end) {
+                if !child.iter_summaries_while_recursive(summary, f) {
+                    return false;
+                }
+                continue;
+            }
+            *summary = child_end;
+        }
+        true
+    }
+
+    /// Folds the items in order, starting from `init`. Folding stops at the first item for which
+    /// `f` returns `ControlFlow::Break`, whose value is returned without visiting the remaining
+    /// items.
+    pub fn fold_items<B, F>(&self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, &T) -> ControlFlow<B, B>,
+    {
+        let mut acc = init;
+        for item in self.iter() {
+            match f(acc, item) {
+                ControlFlow::Continue(next_acc) => acc = next_acc,
+                ControlFlow::Break(result) => return result,
+            }
+        }
+        acc
+    }
+
+    /// Slides a window of the given width along dimension `D` over the tree, one item at a time,
+    /// and yields the combined summary of the items starting inside each window. Like
+    /// `slice::windows`, windows extending past the end of the tree are not yielded. Two cursors
+    /// mark the ends of the window and only ever move forward, but since summaries can't be
+    /// subtracted, each window's summary is folded from the cached summaries of the leaves it
+    /// contains. Panics if `width` is zero.
+    pub fn windows_summaries<D>(&self, width: D) -> WindowsSummaries<T, D>
+    where
+        D: Dimension<Summary = T::Summary>,
+    {
+        assert!(width > D::default(), "Window width must be non-zero");
+
+        let mut start_cursor = self.cursor();
+        start_cursor.seek(&D::default(), SeekBias::Left);
+        let mut end_cursor = self.cursor();
+        end_cursor.seek(&D::default(), SeekBias::Left);
+        WindowsSummaries {
+            start_cursor,
+            end_cursor,
+            width,
+            len: self.len::<D>(),
+            window: VecDeque::new(),
+        }
+    }
+
+    /// Cuts the tree into chunks at most `budget` long along dimension `D`, starting from the end,
+    /// and yields them from last to first. Chunks end between items, so only an item wider than
+    /// `budget` yields a chunk exceeding it. Every chunk is sliced out of the tree with a few
+    /// seeks, sharing the tree's subtrees. Panics if `budget` is zero.
+    pub fn rchunks_by<D>(&self, budget: D) -> RChunksBy<T, D>
+    where
+        D: Dimension<Summary = T::Summary> + for<'b> Sub<&'b D, Output = D>,
+    {
+        assert!(budget > D::default(), "Chunk budget must be non-zero");
+
+        RChunksBy {
+            cursor: self.cursor(),
+            budget,
+            end: if self.is_empty() {
+                None
+            } else {
+                Some(self.len::<D>())
+            },
+        }
+    }
+
+    /// Yields each maximal run of equal adjacent items as the run's first item and its length,
+    /// visiting every item once.
+    pub fn runs(&self) -> Runs<T> {
+        Runs {
+            iter: self.iter().peekable(),
+        }
+    }
+
+    /// Yields the leaves of the tree from left to right. Each leaf is a tree holding a single item,
+    /// which can be cloned in O(1) to share the item without copying it.
+    pub fn leaf_trees(&self) -> impl Iterator<Item = &Tree<T>> {
+        self.subtrees_at_height(0)
+    }
+
+    /// Yields the cached summary of each item in order, without visiting the items themselves.
+    /// Unlike `iter_summaries`, these are the summaries of individual items, not running totals.
+    pub fn summaries(&self) -> impl Iterator<Item = &T::Summary> {
+        self.leaf_trees().map(|leaf| leaf.summary())
+    }
+
+    /// Calls `f` with each subtree whose children are leaves, from left to right. Each subtree
+    /// holds a run of at most `max_children` items, whose combined summary is the subtree's
+    /// `summary()` and whose leaves can be visited with `leaf_trees()`. This is
+    /// `subtrees_at_height(1)` in callback form, for consumers that process items in batches.
+    pub fn for_each_height1_subtree<F: FnMut(&Tree<T>)>(&self, f: F) {
+        self.subtrees_at_height(1).for_each(f);
+    }
+
+    /// Yields the subtrees at the given height from left to right, where leaves have height 0
+    /// and their parents height 1. Together the subtrees contain all items of the tree in order,
+    /// unless `height` exceeds the height of the tree, in which case nothing is yielded. Cloning
+    /// a yielded subtree only bumps a reference count, so the subtrees can cheaply be handed out
+    /// to other threads.
+    pub fn subtrees_at_height(&self, height: u16) -> SubtreesAtHeight<T> {
+        SubtreesAtHeight {
+            height,
+            stack: if self.is_empty() {
+                Vec::new()
+            } else {
+                vec![self]
+            },
+        }
+    }
+
+    /// Returns a cursor over the tree. The cursor's stack is allocated once with room for a path
+    /// from the root to a leaf, so reusing a cursor for many seeks never reallocates it.
+    pub fn cursor(&self) -> Cursor<T> {
+        Cursor::new(self)
+    }
+
+    pub fn len<D: Dimension<Summary = T::Summary>>(&self) -> D {
+        D::from_summary(self.summary())
+    }
+
+    /// Accumulates dimension `D` item by item and returns the first running value for which
+    /// `f` returns true, or the tree's total length if it never does. `f` must be monotone,
+    /// i.e. once it returns true it must keep returning true for all larger values. This allows
+    /// subtrees whose end position doesn't satisfy `f` to be skipped without visiting them.
+    pub fn scan_until<D, F>(&self, mut f: F) -> D
+    where
+        D: Dimension<Summary = T::Summary>,
+        F: FnMut(&D) -> bool,
+    {
+        let mut position = D::default();
+        if !f(&position) {
+            self.scan_until_recursive(&mut position, &mut f);
+        }
+        position
+    }
+
+    fn scan_until_recursive<D, F>(&self, position: &mut D, f: &mut F) -> bool
+    where
+        D: Dimension<Summary = T::Summary>,
+        F: FnMut(&D) -> bool,
+    {
+        for child in self.children() {
+            let child_end = position.clone() + &D::from_summary(child.summary());
+            if f(&child_end) {
+                if child.height() == 0 {
+                    *position = child_end;
+                } else {
+                    child.scan_until_recursive(position, f);
+                }
+                return true;
+            }
+            *position = child_end;
+        }
+        false
+    }
+
+    pub fn debug_shape(&self) -> ShapeNode {
+        match self.0.as_ref() {
+            &Node::Internal {
+                height,
+                ref children,
+                ..
+            } => ShapeNode::Internal {
+                height,
+                children: children.iter().map(|child| child.debug_shape()).collect(),
+            },
+            &Node::Leaf { .. } => ShapeNode::Leaf,
+        }
+    }
+
+    /// Returns how full the internal nodes are on average, as the number of children they hold
+    /// divided by the number they could hold at most. Trees built with `extend_balanced` report
+    /// values close to 1, while trees that saw many splits and removals report lower ones. An
+    /// empty tree reports 0, while a single leaf, such as those yielded by `leaf_trees`, is
+    /// always full and reports 1. This visits every internal node, but no leaf.
+    pub fn fill_ratio(&self) -> f64 {
+        if self.height() == 0 {
+            return 1.0;
+        }
+
+        let max_children = self.config().max_children;
+        let mut internal_nodes = 0;
+        let mut children_count = 0;
+        let mut stack = vec![self];
+        while let Some(tree) = stack.pop() {
+            internal_nodes += 1;
+            children_count += tree.children().len();
+            if tree.height() > 1 {
+                stack.extend(tree.children());
+            }
+        }
+        children_count as f64 / (internal_nodes * max_children) as f64
+    }
+
+    /// Returns the height of the highest node starting at `pos`, or `None` if `pos` doesn't fall on
+    /// the start of any item. Leaves have height 0, so the result tells how large a subtree
+    /// starting exactly at `pos` can be shared, e.g. as a cache key.
+    pub fn boundary_height<D: Dimension<Summary = T::Summary>>(&self, pos: &D) -> Option<u16> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut node = self;
+        let mut node_start = D::default();
+        loop {
+            if node_start == *pos {
+                return Some(node.height());
+            }
+
+            match node.0.as_ref() {
+                &Node::Internal { ref children, .. } => {
+                    let mut containing_child = None;
+                    for child in children {
+                        let child_end = node_start.clone() + &D::from_summary(child.summary());
+                        if node_start == *pos || *pos < child_end {
+                            containing_child = Some(child);
+                            break;
+                        }
+                        node_start = child_end;
+                    }
+                    node = containing_child?;
+                }
+                &Node::Leaf { .. } => return None,
+            }
+        }
+    }
+
+    /// Returns the tree's only item, or `None` if it has zero or several items. This is a fast path
+    /// for one-item trees that follows single-child nodes down instead of creating an iterator.
+    pub fn single(&self) -> Option<&T> {
+        let mut tree = self;
+        loop {
+            match tree.0.as_ref() {
+                &Node::Internal { ref children, .. } if children.len() == 1 => tree = &children[0],
+                &Node::Internal { .. } => return None,
+                &Node::Leaf { ref value, .. } => return Some(value),
+            }
+        }
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.leftmost_leaf().map(|leaf| leaf.value())
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.rightmost_leaf().map(|leaf| leaf.value())
+    }
+
+    /// Returns the summary of the first item. Unlike the rightmost leaf, the leftmost leaf isn't
+    /// cached, so this descends along the left edge of the tree in O(log n).
+    pub fn first_summary(&self) -> Option<&T::Summary> {
+        self.leftmost_leaf().map(|leaf| leaf.summary())
+    }
+
+    /// Returns the summary of the last item in O(1).
+    pub fn last_summary(&self) -> Option<&T::Summary> {
+        self.rightmost_leaf().map(|leaf| leaf.summary())
+    }
+
+    /// Returns the combined summary of the items a cursor moves past when seeking from
+    /// `range.start` to `range.end` with `SeekBias::Right`, i.e. the items that end after
+    /// `range.start` and no later than `range.end`. For a range between item boundaries, these
+    /// are exactly the items inside it.
+    pub fn range_summary<D: Dimension<Summary = T::Summary>>(
+        &self,
+        range: Range<&D>,
+    ) -> T::Summary {
+        let mut cursor = self.cursor();
+        cursor.seek(range.start, SeekBias::Right);
+        cursor.seek_and_summary(range.end, SeekBias::Right)
+    }
+
+    /// Returns the extent along `D` of the items covered by `range_summary`. For a range between
+    /// item boundaries this is the range's length, but computing it needs no subtraction and
+    /// doesn't allocate.
+    pub fn span<D: Dimension<Summary = T::Summary>>(&self, range: Range<&D>) -> D {
+        D::from_summary(&self.range_summary(range))
+    }
+
+    /// Yields the same items as `get_range_as_vec` without cloning them. Unlike a cursor, the
+    /// iterator only keeps a stack of child indices and its position along `D`, rather than the
+    /// full summary of everything before it, so it is cheaper to advance in tight loops.
+    pub fn items_in_range<D: Dimension<Summary = T::Summary>>(
+        &self,
+        range: Range<&D>,
+    ) -> RangeItems<T, D> {
+        let mut stack = Vec::with_capacity(self.height() as usize);
+        let mut position = D::default();
+        let mut tree = self;
+        while let &Node::Internal { ref children, .. } = tree.0.as_ref() {
+            let mut containing_child = None;
+            for (index, child) in children.iter().enumerate() {
+                let child_end = position.clone() + &D::from_summary(child.summary());
+                if child_end > *range.start {
+                    containing_child = Some((index, child));
+                    break;
+                }
+                position = child_end;
+            }
+
+            if let Some((index, child)) = containing_child {
+                stack.push((tree, index));
+                tree = child;
+            } else {
+                stack.clear();
+                break;
+            }
+        }
+
+        RangeItems {
+            stack,
+            position,
+            end: range.end.clone(),
+        }
+    }
+
+    /// Returns the item containing `pos`, i.e. the first item ending after it. Returns an error
+    /// carrying `pos` and the tree's length if no item ends after `pos`.
+    pub fn try_get<D: Dimension<Summary = T::Summary>>(
+        &self,
+        pos: &D,
+    ) -> Result<&T, OutOfRange<D>> {
+        let mut cursor = self.cursor();
+        cursor.seek(pos, SeekBias::Right);
+        cursor.item().ok_or_else(|| OutOfRange {
+            position: pos.clone(),
+            len: self.len::<D>(),
+        })
+    }
+
+    /// Clones the items that overlap `range` into a vector, i.e. those ending after `range.start`
+    /// and starting before `range.end`. This includes an item straddling `range.start`, even
+    /// though it starts before the range.
+    pub fn get_range_as_vec<D: Dimension<Summary = T::Summary>>(&self, range: Range<&D>) -> Vec<T> {
+        let mut items = Vec::new();
+        let mut cursor = self.cursor();
+        cursor.seek(range.start, SeekBias::Right);
+        while let Some(item) = cursor.item() {
+            if cursor.start::<D>() >= *range.end {
+                break;
+            }
+            items.push(item.clone());
+            cursor.next();
+        }
+        items
+    }
+
+    /// Returns the longest range, measured in `D`, of consecutive items whose summaries satisfy
+    /// `f`, or the first such range if there are several. Returns an empty range at the start of
+    /// the tree if no item satisfies `f`. This visits the summary of every item.
+    pub fn longest_run<D, F>(&self, f: F) -> Range<D>
+    where
+        D: Dimension<Summary = T::Summary>,
+        F: Fn(&T::Summary) -> bool,
+    {
+        let mut longest_run = D::default()..D::default();
+        let mut longest_run_len = D::default();
+        let mut run_start = D::default();
+        let mut run_len = D::default();
+        let mut position = D::default();
+        for summary in self.summaries() {
+            position = position + &D::from_summary(summary);
+            if f(summary) {
+                run_len = run_len + &D::from_summary(summary);
+                if run_len > longest_run_len {
+                    longest_run = run_start.clone()..position.clone();
+                    longest_run_len = run_len.clone();
+                }
+            } else {
+                run_start = position.clone();
+                run_len = D::default();
+            }
+        }
+        longest_run
+    }
+
+    /// Returns the leaves of the items intersecting `range`. Items straddling either end of the
+    /// range are included whole. The leaves are cloned by reference, so they are shared with the
+    /// tree and stay the same node across edits that don't touch them, which makes them usable
+    /// as keys for a per-item cache.
+    pub fn leaf_arcs_in_range<D: Dimension<Summary = T::Summary>>(
+        &self,
+        range: Range<&D>,
+    ) -> Vec<Tree<T>> {
+        let mut leaves = Vec::new();
+        let mut cursor = self.cursor();
+        cursor.seek(range.start, SeekBias::Right);
+        while let Some(leaf) = cursor.cur_leaf() {
+            if cursor.start::<D>() >= *range.end {
+                break;
+            }
+            leaves.push(leaf.clone());
+            cursor.next();
+        }
+        leaves
+    }
+
+    /// Returns the first position, measured in `D`, at which `predicate` holds on the combined
+    /// summary of the items before it, or `None` if it doesn't hold anywhere in the tree, not
+    /// even at its end. `predicate` must be monotone: once it holds for a prefix, it must hold
+    /// for every longer prefix as well, which lets the search descend from the root instead of
+    /// visiting every item.
+    pub fn position_of<D, F>(&self, predicate: F) -> Option<D>
+    where
+        D: Dimension<Summary = T::Summary>,
+        F: Fn(&T::Summary) -> bool,
+    {
+        if predicate(&T::Summary::default()) {
+            return Some(D::default());
+        }
+
+        let mut cursor = self.cursor();
+        cursor.seek_by(
+            |summary| {
+                if predicate(summary) {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                }
+            },
+            SeekBias::Right,
+        );
+        cursor
+            .cur_leaf()
+            .map(|leaf| cursor.start::<D>() + &D::from_summary(leaf.summary()))
+    }
+
+    /// Returns the item with the greatest key, or the first such item if there are several. The
+    /// search descends into the child with the greatest key at each level, so `f` applied to a
+    /// subtree's summary must yield the maximum of `f` over the summaries of its items, as is the
+    /// case when the summary implements `MaxSummary`. Passing a key wrapped in `cmp::Reverse`
+    /// finds the minimum of summaries that track a minimum instead.
+    pub fn max_by_key_summary<K, F>(&self, f: F) -> Option<&T>
+    where
+        K: Ord,
+        F: Fn(&T::Summary) -> K,
+    {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut subtree = self;
+        while subtree.height() > 0 {
+            subtree = &subtree.children()[subtree.max_child_index(&f)];
+        }
+        Some(subtree.value())
+    }
+
+    /// Counts the items matching a predicate that can be evaluated on summaries. `f` returns the
+    /// number of matches in a subtree when its summary determines it, in which case the subtree
+    /// isn't descended into, or `None` otherwise. For a single item's summary, `f` must always
+    /// return a count.
+    pub fn count_matching<F: Fn(&T::Summary) -> Option<usize>>(&self, f: F) -> usize {
+        let mut count = 0;
+        let mut stack = if self.is_empty() { vec![] } else { vec![self] };
+        while let Some(subtree) = stack.pop() {
+            match f(subtree.summary()) {
+                Some(subtree_count) => count += subtree_count,
+                None => match subtree.0.as_ref() {
+                    &Node::Internal { ref children, .. } => stack.extend(children.iter()),
+                    &Node::Leaf { .. } => {
+                        panic!("Summary of a single item must determine its count")
+                    }
+                },
+            }
+        }
+        count
+    }
+
+    /// Returns the item with the greatest key as tracked by the tree's `MaxSummary`.
+    pub fn peek_max(&self) -> Option<&T>
+    where
+        T::Summary: MaxSummary,
+    {
+        self.max_by_key_summary(MaxSummary::max_key)
+    }
+
+    /// Removes and returns the item with the greatest key as tracked by the tree's `MaxSummary`.
+    /// The subtrees to the left and right of the removed item are reassembled by pushing them
+    /// onto a new tree, which takes O(log n) pushes.
+    pub fn pop_max(&mut self) -> Option<T>
+    where
+        T::Summary: MaxSummary,
+    {
+        if self.is_empty() {
+            return None;
+        }
+
+        let (tree, item) = {
+            let mut tree = Self::with_config(self.config());
+            let mut right_siblings = Vec::new();
+            let mut subtree = &*self;
+            while subtree.height() > 0 {
+                let index = subtree.max_child_index(&MaxSummary::max_key);
+                let children = subtree.children();
+                for child in &children[..index] {
+                    tree.push_tree(child.clone());
+                }
+                right_siblings.push(&children[index + 1..]);
+                subtree = &children[index];
+            }
+            for child in right_siblings
+                .into_iter()
+                .rev()
+                .flat_map(|children| children)
+            {
+                tree.push_tree(child.clone());
+            }
+            (tree, subtree.value().clone())
+        };
+        *self = tree;
+        Some(item)
+    }
+
+    fn max_child_index<K, F>(&self, f: &F) -> usize
+    where
+        K: Ord,
+        F: Fn(&T::Summary) -> K,
+    {
+        let children = self.children();
+        let mut max_index = 0;
+        let mut max_key = f(children[0].summary());
+        for (index, child) in children.iter().enumerate().skip(1) {
+            let key = f(child.summary());
+            if key > max_key {
+                max_index = index;
+                max_key = key;
+            }
+        }
+        max_index
+    }
+
+    /// Returns whether the root's summary equals the combined summaries of all items. This is a
+    /// cheap O(n) check for stale summaries, meant for tests that edit trees in a loop.
+    pub fn summary_is_consistent(&self) -> bool {
+        let mut summary = T::Summary::default();
+        for item in self.iter() {
+            summary += &item.summarize();
+        }
+        summary == *self.summary()
+    }
+
+    /// Returns whether the items are sorted by their extent along dimension `D`, which is how
+    /// sorted trees expose each item's key. This compares the cached summaries of all leaves, so
+    /// it is meant for debug assertions rather than for checks on every operation.
+    pub fn is_sorted_by<D: Dimension<Summary = T::Summary>>(&self) -> bool {
+        let mut keys = self
+            .leaf_trees()
+            .map(|leaf| D::from_summary(leaf.summary()));
+        if let Some(mut prev_key) = keys.next() {
+            for key in keys {
+                if key < prev_key {
+                    return false;
+                }
+                prev_key = key;
+            }
+        }
+        true
+    }
+
+    /// Returns whether the tree contains `item`. This is a linear scan.
+    pub fn contains(&self, item: &T) -> bool {
+        self.iter().any(|other| other == item)
+    }
+
+    /// Returns the index of the first occurrence of `item`. This is a linear scan.
+    pub fn position(&self, item: &T) -> Option<usize> {
+        self.iter().position(|other| other == item)
+    }
+
+    /// Compares the items of two trees using `eq`. Subtrees shared by both trees are skipped
+    /// without being visited, so `eq` is assumed to be reflexive.
+    pub fn eq_by<F: FnMut(&T, &T) -> bool>(&self, other: &Self, mut eq: F) -> bool {
+        let mut self_stack = vec![self];
+        let mut other_stack = vec![other];
+        loop {
+            match (self_stack.pop(), other_stack.pop()) {
+                (None, None) => return true,
+                (Some(self_subtree), Some(other_subtree)) => {
+                    if Arc::ptr_eq(&self_subtree.0, &other_subtree.0) {
+                        continue;
+                    }
+
+                    let self_height = self_subtree.height();
+                    let other_height = other_subtree.height();
+                    if self_height == 0 && other_height == 0 {
+                        if !eq(self_subtree.value(), other_subtree.value()) {
+                            return false;
+                        }
+                    } else {
+                        // Descend into the taller subtree (or both if they are equally tall)
+                        // until the two sides line up.
+                        if self_height >= other_height {
+                            self_stack.extend(self_subtree.children().iter().rev());
+                        } else {
+                            self_stack.push(self_subtree);
+                        }
+                        if other_height >= self_height {
+                            other_stack.extend(other_subtree.children().iter().rev());
+                        } else {
+                            other_stack.push(other_subtree);
+                        }
+                    }
+                }
+                _ => return false,
+            }
+        }
+    }
+
+    /// Returns the extent of the longest prefix of items shared by both trees, measured in `D`.
+    /// Subtrees shared by both trees are skipped without comparing their items, so for an edited
+    /// clone of a tree this only visits the nodes along the path to the first edit.
+    pub fn common_prefix_len<D: Dimension<Summary = T::Summary>>(&self, other: &Self) -> D {
+        self.common_len(other, false)
+    }
+
+    /// Like `common_prefix_len`, but for the longest shared suffix.
+    pub fn common_suffix_len<D: Dimension<Summary = T::Summary>>(&self, other: &Self) -> D {
+        self.common_len(other, true)
+    }
+
+    /// Returns the edits that turn this tree into `other`, with positions expressed in dimension
+    /// `D` as expected by `apply_edits`. The changed region is narrowed down with
+    /// `common_prefix_len` and `common_suffix_len`, so diffing a tree against an edited clone only
+    /// visits the nodes around the edits. Everything in between is reported as a single
+    /// replacement, which may cover unchanged items. Assumes that every item has a non-zero
+    /// extent in `D`, since items without one can't be told apart by position.
+    pub fn diff<D>(&self, other: &Self) -> Vec<Edit<D, T>>
+    where
+        D: Dimension<Summary = T::Summary> + for<'b> Sub<&'b D, Output = D>,
+    {
+        let prefix_len = self.common_prefix_len::<D>(other);
+        let (self_rest, other_rest) = {
+            let mut self_cursor = self.cursor();
+            self_cursor.seek(&prefix_len, SeekBias::Right);
+            let mut other_cursor = other.cursor();
+            other_cursor.seek(&prefix_len, SeekBias::Right);
+            (
+                self_cursor.slice(&self.len::<D>(), SeekBias::Right),
+                other_cursor.slice(&other.len::<D>(), SeekBias::Right),
+            )
+        };
+        let suffix_len = self_rest.common_suffix_len::<D>(&other_rest);
+        let removed_len = self_rest.len::<D>() - &suffix_len;
+        let inserted_len = other_rest.len::<D>() - &suffix_len;
+
+        let mut edits = Vec::new();
+        if inserted_len > D::default() {
+            edits.push(Edit::Insert {
+                position: prefix_len.clone(),
+                items: other_rest.get_range_as_vec(&D::default()..&inserted_len),
+            });
+        }
+        if removed_len > D::default() {
+            let removed_end = prefix_len.clone() + &removed_len;
+            edits.push(Edit::Remove {
+                range: prefix_len..removed_end,
+            });
+        }
+        edits
+    }
+
+    /// Returns the maximal ranges of this tree, measured in `D`, that are made up of subtrees
+    /// `other` shares, e.g. because one tree is an edited clone of the other. The ranges refer to
+    /// this tree's positions, which may differ from the positions of the same items in `other`
+    /// if an edit changed the length of what precedes them. A shared subtree is shared in its
+    /// entirety, so both trees are only descended until their subtrees match up, and the work
+    /// is proportional to the number of nodes the trees don't share.
+    pub fn shared_regions<D: Dimension<Summary = T::Summary>>(
+        &self,
+        other: &Self,
+    ) -> Vec<Range<D>> {
+        let node_ptr = |tree: &Tree<T>| tree.0.as_ref() as *const Node<T>;
+
+        // Descend both trees one height at a time, comparing each side's nodes with the other
+        // side's nodes of the same height, since only those can be the same node.
+        let mut shared_nodes = HashSet::new();
+        let mut self_frontier = vec![self];
+        let mut other_frontier = vec![other];
+        let mut height = cmp::max(self.height(), other.height());
+        loop {
+            let self_level = self_frontier
+                .iter()
+                .filter(|tree| tree.height() == height)
+                .map(|&tree| node_ptr(tree))
+                .collect::<HashSet<_>>();
+            let other_level = other_frontier
+                .iter()
+                .filter(|tree| tree.height() == height)
+                .map(|&tree| node_ptr(tree))
+                .collect::<HashSet<_>>();
+            shared_nodes.extend(self_level.intersection(&other_level).cloned());
+            if height == 0 {
+                break;
+            }
+
+            for frontier in &mut [&mut self_frontier, &mut other_frontier] {
+                let mut next_frontier = Vec::with_capacity(frontier.len());
+                for &tree in frontier.iter() {
+                    if tree.height() < height {
+                        next_frontier.push(tree);
+                    } else if !shared_nodes.contains(&node_ptr(tree)) {
+                        next_frontier.extend(tree.children());
+                    }
+                }
+                **frontier = next_frontier;
+            }
+            height -= 1;
+        }
+
+        let mut regions: Vec<Range<D>> = Vec::new();
+        let mut position = D::default();
+        let mut stack = vec![self];
+        while let Some(tree) = stack.pop() {
+            let is_shared = shared_nodes.contains(&node_ptr(tree));
+            if is_shared || tree.height() == 0 {
+                let end = position.clone() + &D::from_summary(tree.summary());
+                if is_shared && end > position {
+                    let extends_last_region = regions
+                        .last()
+                        .map_or(false, |region| region.end == position);
+                    if extends_last_region {
+                        regions.last_mut().unwrap().end = end.clone();
+                    } else {
+                        regions.push(position..end.clone());
+                    }
+                }
+                position = end;
+            } else {
+                stack.extend(tree.children().iter().rev());
+            }
+        }
+        regions
+    }
+
+    fn common_len<D: Dimension<Summary = T::Summary>>(&self, other: &Self, from_end: bool) -> D {
+        fn push_children<'a, T: Item>(stack: &mut Vec<&'a Tree<T>>, tree: &'a Tree<T>, rev: bool) {
+            if rev {
+                stack.extend(tree.children().iter());
+            } else {
+                stack.extend(tree.children().iter().rev());
+            }
+        }
+
+        let mut len = D::default();
+        let mut self_stack = vec![self];
+        let mut other_stack = vec![other];
+        while let (Some(self_subtree), Some(other_subtree)) = (self_stack.pop(), other_stack.pop())
+        {
+            if Arc::ptr_eq(&self_subtree.0, &other_subtree.0) {
+                len = len + &D::from_summary(self_subtree.summary());
+                continue;
+            }
+
+            let self_height = self_subtree.height();
+            let other_height = other_subtree.height();
+            if self_height == 0 && other_height == 0 {
+                if self_subtree.value() == other_subtree.value() {
+                    len = len + &D::from_summary(self_subtree.summary());
+                } else {
+                    break;
+                }
+            } else {
+                if self_height >= other_height {
+                    push_children(&mut self_stack, self_subtree, from_end);
+                } else {
+                    self_stack.push(self_subtree);
+                }
+                if other_height >= self_height {
+                    push_children(&mut other_stack, other_subtree, from_end);
+                } else {
+                    other_stack.push(other_subtree);
+                }
+            }
+        }
+        len
+    }
+
+    fn leaf(item: T) -> Self {
+        Tree(Arc::new(Node::Leaf {
+            summary: item.summarize(),
+            value: item,
+        }))
+    }
+
+    /// Builds a tree bottom-up by grouping each level's nodes into as few parents as possible,
+    /// distributing them evenly so that no parent underflows.
+    fn build_balanced<I: IntoIterator<Item = T>>(items: I, config: TreeConfig) -> Self {
+        Self::build_balanced_from_leaves(Self::leaves(items), config)
+    }
+
+    fn try_build_balanced<I: IntoIterator<Item = T>>(
+        items: I,
+        config: TreeConfig,
+    ) -> Result<Self, SummaryOverflow> {
+        Self::try_build_balanced_from_leaves(Self::leaves(items), config)
+    }
+
+    /// Wraps each item in a leaf. Values of a zero-sized type are indistinguishable, so their
+    /// leaves are shared whenever consecutive items have equal summaries, allocating a single
+    /// leaf for a run of markers instead of one per item.
+    fn leaves<I: IntoIterator<Item = T>>(items: I) -> Vec<Self> {
+        let items = items.into_iter();
+        if mem::size_of::<T>() != 0 {
+            return items.map(Self::leaf).collect();
+        }
+
+        let mut leaves: Vec<Self> = Vec::with_capacity(items.size_hint().0);
+        for item in items {
+            let summary = item.summarize();
+            let shared_leaf = match leaves.last() {
+                Some(prev_leaf) if *prev_leaf.summary() == summary => Some(prev_leaf.clone()),
+                _ => None,
+            };
+            leaves.push(shared_leaf.unwrap_or_else(|| {
+                Tree(Arc::new(Node::Leaf {
+                    summary,
+                    value: item,
+                }))
+            }));
+        }
+        leaves
+    }
+
+    fn build_balanced_from_leaves(nodes: Vec<Self>, config: TreeConfig) -> Self {
+        match Self::try_build_balanced_from_leaves(nodes, config) {
+            Ok(tree) => tree,
+            Err(SummaryOverflow) => panic!("Summary overflowed while building a tree"),
+        }
+    }
+
+    fn try_build_balanced_from_leaves(
+        mut nodes: Vec<Self>,
+        config: TreeConfig,
+    ) -> Result<Self, SummaryOverflow> {
+        while nodes.len() > config.max_children {
+            let mut parent_count = (nodes.len() + config.max_children - 1) / config.max_children;
+            let mut parents = Vec::with_capacity(parent_count);
+            let mut remaining = nodes.len();
+            let mut children = nodes.into_iter();
+            while parent_count > 0 {
+                let child_count = (remaining + parent_count - 1) / parent_count;
+                parents.push(Self::try_from_children(
+                    children.by_ref().take(child_count).collect(),
+                    config,
+                )?);
+                remaining -= child_count;
+                parent_count -= 1;
+            }
+            nodes = parents;
+        }
+        Self::try_from_children(nodes, config)
+    }
+
+    /// Builds a balanced tree out of items sorted by their extent along dimension `D`, the same key
+    /// `is_sorted_by` checks. Returns an error if an item's key is smaller than its predecessor's.
+    pub fn try_from_sorted_iter<D, I>(items: I) -> Result<Self, UnsortedError>
+    where
+        D: Dimension<Summary = T::Summary>,
+        I: IntoIterator<Item = T>,
+    {
+        let mut leaves = Vec::new();
+        let mut prev_key: Option<D> = None;
+        for (index, item) in items.into_iter().enumerate() {
+            let leaf = Self::leaf(item);
+            let key = D::from_summary(leaf.summary());
+            if prev_key.map_or(false, |prev_key| key < prev_key) {
+                return Err(UnsortedError { index });
+            }
+            prev_key = Some(key);
+            leaves.push(leaf);
+        }
+        Ok(Self::build_balanced_from_leaves(
+            leaves,
+            TreeConfig::default(),
+        ))
+    }
+
+    /// Returns a balanced tree containing `count` copies of `item`. All copies share a single
+    /// leaf, so `item` is cloned and summarized only once.
+    pub fn repeat(item: T, count: usize) -> Self {
+        let leaf = Self::leaf(item);
+        Self::build_balanced_from_leaves(vec![leaf; count], TreeConfig::default())
+    }
+
+    /// Returns a balanced tree of copies of `item` that is at least `len` long along dimension
+    /// `D`. If `len` isn't a multiple of the item's extent, the tree overshoots it by less than
+    /// one item. Like `repeat`, all copies share a single leaf. Panics if `len` is non-zero and
+    /// `item` has a zero extent in `D`.
+    pub fn fill<D: Dimension<Summary = T::Summary>>(len: D, item: T) -> Self {
+        let mut tree = Self::new();
+        tree.resize(len, item);
+        tree
+    }
+
+    /// Concatenates the given trees in order. Each part is pushed as a whole rather than item by
+    /// item, so its subtrees end up shared with the result, except for the nodes along its edges
+    /// that have to be rebalanced.
+    pub fn concat_all(parts: Vec<Self>) -> Self {
+        let mut parts = parts.into_iter();
+        let mut result = match parts.next() {
+            Some(first_part) => first_part,
+            None => return Self::new(),
+        };
+        for part in parts {
+            result.push_tree(part);
+        }
+        result
+    }
+
+    /// Returns a balanced tree alternating between the items of `self` and `other`, starting with
+    /// `self`. Once either tree runs out of items, the remaining items of the other follow.
+    pub fn interleave(&self, other: &Self) -> Self {
+        let mut self_items = self.iter();
+        let mut other_items = other.iter();
+        let mut items = Vec::new();
+        loop {
+            match self_items.next() {
+                Some(item) => items.push(item.clone()),
+                None => {
+                    items.extend(other_items.cloned());
+                    break;
+                }
+            }
+            match other_items.next() {
+                Some(item) => items.push(item.clone()),
+                None => {
+                    items.extend(self_items.cloned());
+                    break;
+                }
+            }
+        }
+        Self::build_balanced(items, self.config())
+    }
+
+    /// Returns a balanced tree of `f` applied to each pair of items at the same index in `self`
+    /// and `other`. Panics if the trees contain different numbers of items.
+    pub fn zip_map<U, V, F>(&self, other: &Tree<U>, f: F) -> Tree<V>
+    where
+        U: Item,
+        V: Item,
+        F: Fn(&T, &U) -> V,
+    {
+        let mut self_items = self.iter();
+        let mut other_items = other.iter();
+        let mut items = Vec::new();
+        loop {
+            match (self_items.next(), other_items.next()) {
+                (Some(self_item), Some(other_item)) => items.push(f(self_item, other_item)),
+                (None, None) => break,
+                _ => panic!("Tried to zip trees with different numbers of items"),
+            }
+        }
+        Tree::build_balanced(items, self.config())
+    }
+
+    /// Returns a balanced tree of `f` applied to each item, or the first error `f` returns. No
+    /// item after the one that failed is passed to `f`.
+    pub fn try_map<U, E, F>(&self, f: F) -> Result<Tree<U>, E>
+    where
+        U: Item,
+        F: Fn(&T) -> Result<U, E>,
+    {
+        let items = self.iter().map(f).collect::<Result<Vec<_>, _>>()?;
+        Ok(Tree::build_balanced(items, self.config()))
+    }
+
+    /// Like `Iterator::scan`, threads the state `init` through `f` for each item in order, and
+    /// returns a balanced tree of the values `f` produces, e.g. turning a tree of deltas into a
+    /// tree of absolute positions.
+    pub fn scan_into<U, S, F>(&self, init: S, mut f: F) -> Tree<U>
+    where
+        U: Item,
+        F: FnMut(&mut S, &T) -> U,
+    {
+        let mut state = init;
+        let items = self.iter().map(|item| f(&mut state, item));
+        Tree::build_balanced(items, self.config())
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.push_tree(Self::leaf(item))
+    }
+
+    /// Passes the last item to `f` to be modified in place, then updates the summaries along the
+    /// right spine of the tree. Nodes shared with other trees are copied first, so clones of the
+    /// tree are unaffected. Returns false without calling `f` if the tree is empty.
+    pub fn update_last<F: FnOnce(&mut T)>(&mut self, f: F) -> bool {
+        if self.is_empty() {
+            false
+        } else {
+            self.update_last_recursive(f);
+            true
+        }
+    }
+
+    fn update_last_recursive<F: FnOnce(&mut T)>(&mut self, f: F) {
+        match Arc::make_mut(&mut self.0) {
+            &mut Node::Internal {
+                ref mut children,
+                ref mut summary,
+                ref mut rightmost_leaf,
+                ..
+            } => {
+                // Drop the cached reference to the last leaf so that it isn't copied needlessly.
+                rightmost_leaf.take();
+                children.last_mut().unwrap().update_last_recursive(f);
+                *summary = Self::summarize_children(children);
+                *rightmost_leaf = children.last().unwrap().rightmost_leaf().cloned();
+            }
+            &mut Node::Leaf {
+                ref mut summary,
+                ref mut value,
+            } => {
+                f(value);
+                *summary = value.summarize();
+            }
+        }
+    }
+
+    pub fn push_tree(&mut self, other: Self) {
+        if other.is_empty() {
+            return;
+        }
+
+        let self_height = self.height();
+        let other_height = other.height();
+
+        // Other is a taller tree, push its children one at a time
+        if self_height < other_height {
+            for other_child in other.children().iter().cloned() {
+                self.push_tree(other_child);
+            }
+            return;
+        }
+
+        // Self is an internal node. Pushing other could cause the root to split.
+        if let Some(split) = self.push_recursive(other) {
+            let config = self.config();
+            *self = Self::from_children(vec![self.clone(), split], config)
+        }
+    }
+
+    /// Appends `items` like `extend`, but builds them into a balanced tree first and pushes it in
+    /// a single merge rather than pushing the items one at a time.
+    pub fn extend_balanced<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        let config = self.config();
+        self.push_tree(Self::build_balanced(items, config));
+    }
+
+    /// Behaves like `extend_balanced`, but combines summaries with `Item::checked_add_summary`.
+    /// If any summary overflows, returns an error and leaves the tree unchanged.
+    pub fn try_extend_balanced<I: IntoIterator<Item = T>>(
+        &mut self,
+        items: I,
+    ) -> Result<(), SummaryOverflow> {
+        let config = self.config();
+        let other = Self::try_build_balanced(items, config)?;
+        T::checked_add_summary(&mut self.summary().clone(), other.summary())?;
+        self.push_tree(other);
+        Ok(())
+    }
+
+    /// Inserts `items` in order in front of the tree's current items. Like `extend_balanced`,
+    /// the items are built into a balanced tree first, which the current contents are then merged
+    /// into.
+    pub fn extend_front<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        let config = self.config();
+        let mut front = Self::build_balanced(items, config);
+        front.push_tree(self.clone());
+        *self = front;
+    }
+
+    /// Moves all of `other`'s items to the end of this tree, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        let config = other.config();
+        self.push_tree(mem::replace(other, Tree::with_config(config)));
+    }
+
+    fn push_recursive(&mut self, other: Tree<T>) -> Option<Tree<T>> {
+        *self.summary_mut() += other.summary();
+        *self.rightmost_leaf_mut() = other.rightmost_leaf().cloned();
+
+        let self_height = self.height();
+        let other_height = other.height();
+
+        if other_height == self_height {
+            self.append_children(other.children())
+        } else if other_height == self_height - 1 && !other.underflowing() {
+            self.append_children(&[other])
+        } else {
+            if let Some(split) = self.last_child_mut().push_recursive(other) {
+                self.append_children(&[split])
+            } else {
+                None
+            }
+        }
+    }
+
+    fn append_children(&mut self, new_children: &[Tree<T>]) -> Option<Tree<T>> {
+        match Arc::make_mut(&mut self.0) {
+            &mut Node::Internal {
+                ref mut children,
+                ref mut summary,
+                ref mut rightmost_leaf,
+                config,
+                ..
+            } => {
+                let child_count = children.len() + new_children.len();
+                if child_count > config.max_children {
+                    let midpoint = (child_count + child_count % 2) / 2;
+                    let (left_children, right_children): (
+                        Vec<Tree<T>>,
+                        Vec<Tree<T>>,
+                    ) = {
+                        let mut all_children = children.iter().chain(new_children.iter()).cloned();
+                        (
+                            all_children.by_ref().take(midpoint).collect(),
+                            all_children.collect(),
+                        )
+                    };
+                    *children = left_children;
+                    *summary = Self::summarize_children(children);
+                    *rightmost_leaf = children.last().unwrap().rightmost_leaf().cloned();
+                    Some(Tree::from_children(right_children, config))
+                } else {
+                    children.extend(new_children.iter().cloned());
+                    None
+                }
+            }
+            &mut Node::Leaf { .. } => panic!("Tried to append children to a leaf node"),
+        }
+    }
+
+    /// Truncates the tree to the items before `pos` and returns the remaining items.
+    pub fn split_off<D: Dimension<Summary = T::Summary>>(&mut self, pos: &D) -> Self {
+        let (prefix, suffix) = {
+            let mut cursor = self.cursor();
+            let prefix = cursor.slice(pos, SeekBias::Right);
+            let suffix = cursor.slice(&self.len::<D>(), SeekBias::Right);
+            (prefix, suffix)
+        };
+        *self = prefix;
+        suffix
+    }
+
+    /// Splits the tree into the items before and after `pos` without splitting an item. If `pos`
+    /// falls inside an item, the split snaps to the item's start when `bias` is `Left` or to its
+    /// end when `bias` is `Right`.
+    pub fn split_at_item_boundary<D: Dimension<Summary = T::Summary>>(
+        &self,
+        pos: &D,
+        bias: SeekBias,
+    ) -> (Self, Self) {
+        let mut cursor = self.cursor();
+        let mut prefix = cursor.slice(pos, SeekBias::Right);
+        let start = cursor.start::<D>();
+        if bias == SeekBias::Right && start < *pos {
+            if let Some(item) = cursor.item() {
+                let end = start + &D::from_summary(&item.summarize());
+                prefix.push_tree(cursor.slice(&end, SeekBias::Right));
+            }
+        }
+        let suffix = cursor.slice(&self.len::<D>(), SeekBias::Right);
+        (prefix, suffix)
+    }
+
+    /// Removes and returns the longest prefix for which `f` holds on the combined summary of the
+    /// prefix's items, leaving the remaining items in the tree. `f` must be monotone: once it
+    /// returns false for a prefix, it must return false for every longer prefix as well. This
+    /// allows whole subtrees to be taken without evaluating `f` for each of their items.
+    pub fn take_prefix_while<F: Fn(&T::Summary) -> bool>(&mut self, f: F) -> Self {
+        let mut prefix = Self::with_config(self.config());
+        let mut suffix = Self::with_config(self.config());
+        {
+            let mut cursor = self.cursor();
+            let cmp = |summary: &T::Summary| {
+                if f(summary) {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            };
+            cursor.seek_and_slice(&SeekByComparator(cmp), SeekBias::Right, &mut prefix);
+            let end = SeekByComparator(|_: &T::Summary| Ordering::Less);
+            cursor.seek_and_slice(&end, SeekBias::Right, &mut suffix);
+        }
+        *self = suffix;
+        prefix
+    }
+
+    /// Removes the items in `range` from the tree and returns them as a tree of their own. The
+    /// prefix, the removed items and the suffix are all sliced during a single cursor walk.
+    /// Panics if the range is reversed or extends past the end of the tree.
+    pub fn take_range<D: Dimension<Summary = T::Summary>>(&mut self, range: Range<&D>) -> Self {
+        let len = match self.check_splice_range(&range) {
+            Ok(len) => len,
+            Err(error) => panic!("Invalid range: {:?}", error),
+        };
+        let (mut prefix, taken, suffix) = {
+            let mut cursor = self.cursor();
+            let prefix = cursor.slice(range.start, SeekBias::Right);
+            let taken = cursor.slice(range.end, SeekBias::Right);
+            let suffix = cursor.slice(&len, SeekBias::Right);
+            (prefix, taken, suffix)
+        };
+        prefix.push_tree(suffix);
+        *self = prefix;
+        taken
+    }
+
+    /// Discards everything outside of `range`, leaving the tree equal to the slice of items that
+    /// `take_range` would have returned. Panics if the range is reversed or extends past the end
+    /// of the tree.
+    pub fn retain_range<D: Dimension<Summary = T::Summary>>(&mut self, range: Range<&D>) {
+        if let Err(error) = self.check_splice_range(&range) {
+            panic!("Invalid range: {:?}", error);
+        }
+        let retained = {
+            let mut cursor = self.cursor();
+            cursor.seek(range.start, SeekBias::Right);
+            cursor.slice(range.end, SeekBias::Right)
+        };
+        *self = retained;
+    }
+
+    /// Cuts the tree into consecutive pieces that are `width` long along dimension `D`, except
+    /// for the last one, which holds the remainder. Cuts are made between items, so an item
+    /// straddling a multiple of `width` starts the next piece, and an item wider than `width` can
+    /// leave empty pieces in front of it. The pieces are sliced out of the tree and share its
+    /// subtrees where possible. Panics if `width` is zero.
+    pub fn split_every<D: Dimension<Summary = T::Summary>>(&self, width: D) -> Vec<Self> {
+        assert!(width > D::default(), "Piece width must be non-zero");
+
+        let mut pieces = Vec::new();
+        let mut cursor = self.cursor();
+        cursor.seek(&D::default(), SeekBias::Left);
+        let mut end = D::default();
+        while cursor.item().is_some() {
+            end = end + &width;
+            pieces.push(cursor.slice(&end, SeekBias::Right));
+        }
+        pieces
+    }
+
+    /// Rotates the tree so that the items from `mid` onwards come first, followed by the items
+    /// before `mid`. This is a split and a concatenation, so it runs in O(log n). Panics if
+    /// `mid` is greater than the tree's length.
+    pub fn rotate_left<D: Dimension<Summary = T::Summary>>(&mut self, mid: &D) {
+        assert!(*mid <= self.len::<D>(), "Rotation out of bounds");
+        let mut suffix = self.split_off(mid);
+        suffix.append(self);
+        *self = suffix;
+    }
+
+    /// Rotates the tree so that the last `k` items come first. Panics if `k` is greater than the
+    /// tree's length.
+    pub fn rotate_right<D>(&mut self, k: &D)
+    where
+        D: Dimension<Summary = T::Summary> + for<'b> Sub<&'b D, Output = D>,
+    {
+        let len = self.len::<D>();
+        assert!(*k <= len, "Rotation out of bounds");
+        self.rotate_left(&(len - k));
+    }
+
+    /// Truncates the tree to `len` like `split_off` if it is longer, or otherwise appends the
+    /// fewest copies of `fill` needed to make it at least `len` long. Panics if the tree needs
+    /// to grow and `fill` has a zero extent in `D`.
+    pub fn resize<D: Dimension<Summary = T::Summary>>(&mut self, len: D, fill: T) {
+        let mut end = self.len::<D>();
+        if len < end {
+            self.split_off(&len);
+        } else if end < len {
+            let fill_len = D::from_summary(&fill.summarize());
+            assert!(
+                fill_len > D::default(),
+                "Fill item must have a non-zero extent"
+            );
+            let mut count = 0;
+            while end < len {
+                end = end + &fill_len;
+                count += 1;
+            }
+            let config = self.config();
+            let leaf = Self::leaf(fill);
+            self.push_tree(Self::build_balanced_from_leaves(vec![leaf; count], config));
+        }
+    }
+
+    /// Replaces the items in `old_range` with `new_items`. Panics if the range is reversed or
+    /// extends past the end of the tree.
+    pub fn splice<D: Dimension<Summary = T::Summary>, I: IntoIterator<Item = T>>(
+        &mut self,
+        old_range: Range<&D>,
+        new_items: I,
+    ) {
+        if let Err(error) = self.try_splice(old_range, new_items) {
+            panic!("Invalid splice range: {:?}", error);
+        }
+    }
+
+    /// Replaces the items in `old_range` with `new_items`, or leaves the tree untouched and
+    /// returns an error if the range is reversed or extends past the end of the tree.
+    pub fn try_splice<D: Dimension<Summary = T::Summary>, I: IntoIterator<Item = T>>(
+        &mut self,
+        old_range: Range<&D>,
+        new_items: I,
+    ) -> Result<(), SpliceError> {
+        let len = self.check_splice_range(&old_range)?;
+        let mut result = Self::with_config(self.config());
+        self.append_subsequence(&mut result, &D::default(), old_range.start);
+        result.extend(new_items);
+        self.append_subsequence(&mut result, old_range.end, &len);
+        *self = result;
+        Ok(())
+    }
+
+    /// Splices like `splice`, and also measures the edit in dimension `D2`: where the replaced
+    /// items started, and how long the replaced and inserted items are. The measurements are read
+    /// off the summaries of the preserved prefix and of the removed items, which are selected the
+    /// same way `splice` selects them. Panics if the range is reversed or extends past the end of
+    /// the tree.
+    pub fn splice_reporting<D1, D2, I>(
+        &mut self,
+        old_range: Range<&D1>,
+        new_items: I,
+    ) -> EditSummary<D2>
+    where
+        D1: Dimension<Summary = T::Summary>,
+        D2: Dimension<Summary = T::Summary>,
+        I: IntoIterator<Item = T>,
+    {
+        let len = match self.check_splice_range(&old_range) {
+            Ok(len) => len,
+            Err(error) => panic!("Invalid splice range: {:?}", error),
+        };
+        let config = self.config();
+        let inserted = Self::build_balanced(new_items, config);
+        let mut removed = Self::with_config(config);
+        self.append_subsequence(&mut removed, old_range.start, old_range.end);
+        let mut result = Self::with_config(config);
+        self.append_subsequence(&mut result, &D1::default(), old_range.start);
+        let edit_summary = EditSummary {
+            start: D2::from_summary(result.summary()),
+            old_len: D2::from_summary(removed.summary()),
+            new_len: D2::from_summary(inserted.summary()),
+        };
+        result.push_tree(inserted);
+        self.append_subsequence(&mut result, old_range.end, &len);
+        *self = result;
+        edit_summary
+    }
+
+    /// Replaces the items in `old_range` with the items of `replacement`. Unlike `splice`, the
+    /// replacement is pushed as a whole, so its nodes are shared rather than rebuilt item by
+    /// item. Panics if the range is reversed or extends past the end of the tree.
+    pub fn splice_tree<D: Dimension<Summary = T::Summary>>(
+        &mut self,
+        old_range: Range<&D>,
+        replacement: Self,
+    ) {
+        let len = match self.check_splice_range(&old_range) {
+            Ok(len) => len,
+            Err(error) => panic!("Invalid splice range: {:?}", error),
+        };
+        let mut result = Self::with_config(self.config());
+        self.append_subsequence(&mut result, &D::default(), old_range.start);
+        result.push_tree(replacement);
+        self.append_subsequence(&mut result, old_range.end, &len);
+        *self = result;
+    }
+
+    /// Replaces several ranges with the items of the corresponding trees, building the result
+    /// once. Ranges refer to the tree before any replacement and must be ascending and
+    /// non-overlapping. Each range selects the same items as it would in `splice_tree`, whose
+    /// replacements are likewise pushed as a whole, so their nodes are shared rather than rebuilt
+    /// item by item. The kept items between the ranges are appended by descending only into the
+    /// nodes that overlap them, so `k` ranges cost O(k log n) plus the size of the result's
+    /// spine. Panics if a range is reversed, extends past the end of the tree or starts before
+    /// the end of the previous one.
+    pub fn splice_many<D: Dimension<Summary = T::Summary>>(&mut self, ops: Vec<(Range<D>, Self)>) {
+        let mut result = Self::with_config(self.config());
+        let mut prev_end = D::default();
+        for (range, replacement) in ops {
+            if let Err(error) = self.check_splice_range(&(&range.start..&range.end)) {
+                panic!("Invalid splice range: {:?}", error);
+            }
+            assert!(
+                prev_end <= range.start,
+                "Splice ranges must be ascending and non-overlapping"
+            );
+            self.append_subsequence(&mut result, &prev_end, &range.start);
+            result.push_tree(replacement);
+            prev_end = range.end;
+        }
+        self.append_subsequence(&mut result, &prev_end, &self.len::<D>());
+        *self = result;
+    }
+
+    /// Removes the item at each of the given positions in a single pass over the tree. Positions
+    /// refer to the tree before any item is removed and must be strictly ascending. The item
+    /// removed for a position is the one containing it, so with a dimension other than an item
+    /// count, each position must lie past the item removed for the previous one. Positions at the
+    /// end of the tree remove nothing.
+    pub fn bulk_remove_sorted<D: Dimension<Summary = T::Summary>>(&mut self, positions: &[D]) {
+        debug_assert!(
+            positions.windows(2).all(|pair| pair[0] < pair[1]),
+            "Positions must be strictly ascending"
+        );
+
+        let len = self.len::<D>();
+        let mut result = Self::with_config(self.config());
+        {
+            let mut cursor = self.cursor();
+            for position in positions {
+                result.push_tree(cursor.slice(position, SeekBias::Right));
+                if cursor.item().is_some() {
+                    cursor.next();
+                }
+            }
+            result.push_tree(cursor.slice(&len, SeekBias::Right));
+        }
+        *self = result;
+    }
+
+    /// Applies a batch of edits in a single pass over the tree. Every position refers to the tree
+    /// as it was before the batch, so the edits must be sorted by position and must not overlap.
+    /// An insertion may share its position with the end of the previous edit or the start of the
+    /// next one. Panics if the edits are out of order, overlap or extend past the end of the tree.
+    pub fn apply_edits<D: Dimension<Summary = T::Summary>>(&mut self, edits: &[Edit<D, T>]) {
+        let len = self.len::<D>();
+        let mut result = Self::with_config(self.config());
+        {
+            let mut cursor = self.cursor();
+            let mut prev_end = D::default();
+            for edit in edits {
+                match edit {
+                    &Edit::Insert {
+                        ref position,
+                        ref items,
+                    } => {
+                        assert!(
+                            prev_end <= *position && *position <= len,
+                            "Inconsistent edit position: {:?}",
+                            position
+                        );
+                        result.push_tree(cursor.slice(position, SeekBias::Right));
+                        result.extend(items.iter().cloned());
+                        prev_end = position.clone();
+                    }
+                    &Edit::Remove { ref range } => {
+                        assert!(
+                            prev_end <= range.start && range.start <= range.end && range.end <= len,
+                            "Inconsistent edit range: {:?}",
+                            range
+                        );
+                        result.push_tree(cursor.slice(&range.start, SeekBias::Right));
+                        cursor.seek_and_summary(&range.end, SeekBias::Right);
+                        prev_end = range.end.clone();
+                    }
+                }
+            }
+            result.push_tree(cursor.slice(&len, SeekBias::Right));
+        }
+        *self = result;
+    }
+
+    fn check_splice_range<D: Dimension<Summary = T::Summary>>(
+        &self,
+        range: &Range<&D>,
+    ) -> Result<D, SpliceError> {
+        let len = D::from_summary(self.summary());
+        if range.start > range.end {
+            Err(SpliceError::RangeReversed)
+        } else if *range.end > len {
+            Err(SpliceError::RangeOutOfBounds)
+        } else {
+            Ok(len)
+        }
+    }
+
+    fn append_subsequence<D: Dimension<Summary = T::Summary>>(
+        &self,
+        result: &mut Self,
+        start: &D,
+        end: &D,
+    ) {
+        self.append_subsequence_recursive(result, D::default(), start, end);
+    }
+
+    fn append_subsequence_recursive<D: Dimension<Summary = T::Summary>>(
+        &self,
+        result: &mut Self,
+        node_start: D,
+        start: &D,
+        end: &D,
+    ) {
+        match self.0.as_ref() {
+            &Node::Internal {
+                ref summary,
+                ref children,
+                ..
+            } => {
+                let node_end = node_start.clone() + &D::from_summary(summary);
+                if *start <= node_start && node_end <= *end {
+                    result.push_tree(self.clone());
+                } else if node_start < *end && *start <= node_end {
+                    let mut child_start = node_start.clone();
+                    for ref child in children {
+                        child.append_subsequence_recursive(result, child_start.clone(), start, end);
+                        child_start = child_start + &D::from_summary(child.summary());
+                    }
+                }
+            }
+            &Node::Leaf { .. } => {
+                if *start <= node_start && node_start < *end {
+                    result.push_tree(self.clone());
+                }
+            }
+        }
+    }
+
+    fn leftmost_leaf(&self) -> Option<&Tree<T>> {
+        let mut tree = self;
+        loop {
+            match tree.0.as_ref() {
+                &Node::Internal { ref children, .. } => tree = children.first()?,
+                &Node::Leaf { .. } => return Some(tree),
+            }
+        }
+    }
+
+    fn rightmost_leaf(&self) -> Option<&Tree<T>> {
+        match self.0.as_ref() {
+            &Node::Internal {
+                ref rightmost_leaf, ..
+            } => rightmost_leaf.as_ref(),
+            &Node::Leaf { .. } => Some(self),
+        }
+    }
+
+    fn rightmost_leaf_mut(&mut self) -> &mut Option<Tree<T>> {
+        match Arc::make_mut(&mut self.0) {
+            &mut Node::Internal {
+                ref mut rightmost_leaf,
+                ..
+            } => rightmost_leaf,
+            _ => {
+                panic!("Requested a mutable reference to the rightmost leaf of a non-internal node")
+            }
+        }
+    }
+
+    pub fn summary(&self) -> &T::Summary {
+        match self.0.as_ref() {
+            &Node::Internal { ref summary, .. } => summary,
+            &Node::Leaf { ref summary, .. } => summary,
+        }
+    }
+
+    /// Returns the address of the root node, which is shared by clones of this tree and can be
+    /// used as a cache key for its contents. The layout of the nodes behind this pointer is an
+    /// implementation detail and may change between versions.
+    #[cfg(feature = "internals")]
+    pub fn root_ptr(&self) -> usize {
+        self.0.as_ref() as *const Node<T> as usize
+    }
+
+    pub fn total_summary(&self) -> T::Summary {
+        self.summary().clone()
+    }
+
+    fn summary_mut(&mut self) -> &mut T::Summary {
+        match Arc::make_mut(&mut self.0) {
+            &mut Node::Internal {
+                ref mut summary, ..
+            } => summary,
+            &mut Node::Leaf {
+                ref mut summary, ..
+            } => summary,
+        }
+    }
+
+    fn children(&self) -> &[Tree<T>] {
+        match self.0.as_ref() {
+            &Node::Internal { ref children, .. } => children.as_slice(),
+            &Node::Leaf { .. } => panic!("Requested children of a leaf node"),
+        }
+    }
+
+    fn last_child_mut(&mut self) -> &mut Tree<T> {
+        match Arc::make_mut(&mut self.0) {
+            &mut Node::Internal {
+                ref mut children, ..
+            } => children.last_mut().unwrap(),
+            &mut Node::Leaf { .. } => panic!("Requested last child of a leaf node"),
+        }
+    }
+
+    fn value(&self) -> &T {
+        match self.0.as_ref() {
+            &Node::Internal { .. } => panic!("Requested value of an internal node"),
+            &Node::Leaf { ref value, .. } => value,
+        }
+    }
+
+    fn underflowing(&self) -> bool {
+        match self.0.as_ref() {
+            &Node::Internal {
+                ref children,
+                config,
+                ..
+            } => children.len() < config.min_children,
+            &Node::Leaf { .. } => false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self.0.as_ref() {
+            &Node::Internal { ref children, .. } => children.len() == 0,
+            &Node::Leaf { .. } => false,
+        }
+    }
+
+    fn config(&self) -> TreeConfig {
+        match self.0.as_ref() {
+            &Node::Internal { config, .. } => config,
+            &Node::Leaf { .. } => TreeConfig::default(),
+        }
+    }
+
+    fn height(&self) -> u16 {
+        match self.0.as_ref() {
+            &Node::Internal { height, .. } => height,
+            &Node::Leaf { .. } => 0,
+        }
+    }
+}
+
+impl SeekBias {
+    /// Returns whether a cursor should move past a subtree, given how the target compares with
+    /// the subtree's end.
+    fn skips(self, target_cmp_end: Ordering) -> bool {
+        match target_cmp_end {
+            Ordering::Greater => true,
+            Ordering::Equal => self == SeekBias::Right,
+            Ordering::Less => false,
+        }
+    }
+}
+
+impl<T: Item> SeekAggregate<T> for () {
+    fn push_subtree(&mut self, _: &Tree<T>) {}
+}
+
+impl<T: Item> SeekAggregate<T> for Tree<T> {
+    fn push_subtree(&mut self, subtree: &Tree<T>) {
+        self.push_tree(subtree.clone());
+    }
+}
+
+impl<T: Item> SeekAggregate<T> for SummarySeekAggregate<T::Summary> {
+    fn push_subtree(&mut self, subtree: &Tree<T>) {
+        self.0 += subtree.summary();
+    }
+}
+
+impl<T: Item, A: SeekAggregate<T>, B: SeekAggregate<T>> SeekAggregate<T> for (A, B) {
+    fn push_subtree(&mut self, subtree: &Tree<T>) {
+        self.0.push_subtree(subtree);
+        self.1.push_subtree(subtree);
+    }
+}
+
+impl<D: Dimension> SeekTarget<D::Summary> for D {
+    fn cmp_end(&self, start: &D::Summary, extent: &D::Summary) -> Ordering {
+        self.cmp(&(D::from_summary(start) + &D::from_summary(extent)))
+    }
+}
+
+impl<S: Clone + for<'a> AddAssign<&'a S>, F: Fn(&S) -> Ordering> SeekTarget<S>
+    for SeekByComparator<F>
+{
+    fn cmp_end(&self, start: &S, extent: &S) -> Ordering {
+        let mut end = start.clone();
+        end += extent;
+        (self.0)(&end).reverse()
+    }
+}
+
+impl Default for TreeConfig {
+    fn default() -> Self {
+        Self {
+            min_children: MIN_CHILDREN,
+            max_children: MAX_CHILDREN,
+        }
+    }
+}
+
+impl<T: Item> TreeBuilder<T> {
+    pub fn min_children(mut self, min_children: usize) -> Self {
+        self.config.min_children = min_children;
+        self
+    }
+
+    pub fn max_children(mut self, max_children: usize) -> Self {
+        self.config.max_children = max_children;
+        self
+    }
+
+    /// Builds an empty tree. Panics unless `max_children >= 2 * min_children - 1`, which
+    /// guarantees that both halves of a split node have at least `min_children` children, and
+    /// unless `max_children >= 2`, since nodes with a single child could never reduce the number
+    /// of subtrees when building a tree bottom-up.
+    pub fn build(self) -> Tree<T> {
+        let TreeConfig {
+            min_children,
+            max_children,
+        } = self.config;
+        assert!(min_children >= 1, "min_children must be at least 1");
+        assert!(max_children >= 2, "max_children must be at least 2");
+        assert!(
+            max_children >= 2 * min_children - 1,
+            "max_children must be at least 2 * min_children - 1"
+        );
+        Tree::with_config(self.config)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T> Tree<T>
+where
+    T: Item + Send + Sync,
+    T::Summary: Send + Sync,
+{
+    /// Computes the same summary as `range_summary`, but recursively splits the children of each
+    /// node in half and summarizes both halves with `rayon::join`. Subtrees lying entirely inside
+    /// the range contribute their cached summaries, and below `PARALLEL_MIN_HEIGHT` the children
+    /// are visited on the current thread. The partial summaries are folded from left to right, so
+    /// summaries whose combination isn't commutative are supported.
+    pub fn par_range_summary<D>(&self, range: Range<&D>) -> T::Summary
+    where
+        D: Dimension<Summary = T::Summary> + Send + Sync,
+    {
+        if self.height() == 0 {
+            return self.range_summary(range);
+        }
+        Self::par_range_summary_recursive(self.children(), &D::default(), &range)
+    }
+
+    fn par_range_summary_recursive<D>(
+        children: &[Tree<T>],
+        children_start: &D,
+        range: &Range<&D>,
+    ) -> T::Summary
+    where
+        D: Dimension<Summary = T::Summary> + Send + Sync,
+    {
+        if children.len() > 1 && children[0].height() >= PARALLEL_MIN_HEIGHT {
+            let (left, right) = children.split_at(children.len() / 2);
+            let mut right_start = children_start.clone();
+            for child in left {
+                right_start = right_start + &D::from_summary(child.summary());
+            }
+            let (mut summary, right_summary) = ::rayon::join(
+                || Self::par_range_summary_recursive(left, children_start, range),
+                || Self::par_range_summary_recursive(right, &right_start, range),
+            );
+            summary += &right_summary;
+            return summary;
+        }
+
+        let mut summary = T::Summary::default();
+        let mut child_start = children_start.clone();
+        for child in children {
+            if child_start > *range.end {
+                break;
+            }
+            let child_end = child_start.clone() + &D::from_summary(child.summary());
+            if child_end > *range.start {
+                if child.height() == 0 {
+                    if child_end <= *range.end {
+                        summary += child.summary();
+                    }
+                } else if child_start > *range.start && child_end <= *range.end {
+                    summary += child.summary();
+                } else {
+                    summary +=
+                        &Self::par_range_summary_recursive(child.children(), &child_start, range);
+                }
+            }
+            child_start = child_end;
+        }
+        summary
+    }
+}
+
+/// Builds a balanced subtree out of each chunk of items that rayon hands to a thread, then pushes
+/// the subtrees in order, so the result has the same items as a sequential `extend`.
+#[cfg(feature = "rayon")]
+impl<T> ParallelExtend<T> for Tree<T>
+where
+    T: Item + Send + Sync,
+    T::Summary: Send + Sync,
+{
+    fn par_extend<I: IntoParallelIterator<Item = T>>(&mut self, items: I) {
+        let config = self.config();
+        let subtrees = items
+            .into_par_iter()
+            .fold(Vec::new, |mut chunk, item| {
+                chunk.push(item);
+                chunk
+            })
+            .map(|chunk| Self::build_balanced(chunk, config))
+            .collect::<Vec<_>>();
+        for subtree in subtrees {
+            self.push_tree(subtree);
+        }
+    }
+}
+
+impl<'a, T: 'a + Item> Iter<'a, T> {
+    fn new(tree: &'a Tree<T>) -> Self {
+        Iter {
+            tree,
+            did_start: false,
+            stack: Vec::with_capacity(tree.height() as usize),
+            peeked: None,
+        }
+    }
+
+    /// Returns the item the next call to `next` will yield, without advancing past it. The item
+    /// is remembered, so peeking repeatedly only descends to it once.
+    pub fn peek(&mut self) -> Option<&'a T> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.advance());
+        }
+        self.peeked.unwrap()
+    }
+
+    fn advance(&mut self) -> Option<&'a T> {
+        if self.did_start {
+            while self.stack.len() > 0 {
+                let (tree, index) = {
+                    let &mut (tree, ref mut index) = self.stack.last_mut().unwrap();
+                    *index += 1;
+                    (tree, *index)
+                };
+                if let Some(child) = tree.children().get(index) {
+                    return self.seek_to_first_item(child);
+                } else {
+                    self.stack.pop();
+                }
+            }
+            None
+        } else {
+            self.did_start = true;
+            self.seek_to_first_item(self.tree)
+        }
+    }
+
+    fn seek_to_first_item(&mut self, mut tree: &'a Tree<T>) -> Option<&'a T> {
+        if tree.is_empty() {
+            None
+        } else {
+            loop {
+                match tree.0.as_ref() {
+                    &Node::Internal { ref children, .. } => {
+                        self.stack.push((tree, 0));
+                        tree = &children[0];
+                    }
+                    &Node::Leaf { ref value, .. } => return Some(value),
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T: 'a + Item> Iterator for Iter<'a, T>
+where
+    Self: 'a,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => self.advance(),
+        }
+    }
+}
+
+impl<'a, T: 'a + Item> Iterator for RevIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(subtree) = self.stack.pop() {
+            match subtree.0.as_ref() {
+                &Node::Internal { ref children, .. } => self.stack.extend(children.iter()),
+                &Node::Leaf { ref value, .. } => return Some(value),
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T: 'a + Item> Iterator for SubtreesAtHeight<'a, T> {
+    type Item = &'a Tree<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(subtree) = self.stack.pop() {
+            let subtree_height = subtree.height();
+            if subtree_height == self.height {
+                return Some(subtree);
+            } else if subtree_height > self.height {
+                self.stack.extend(subtree.children().iter().rev());
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T: 'a + Item> Iterator for Runs<'a, T> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next()?;
+        let mut len = 1;
+        while self.iter.peek() == Some(&item) {
+            self.iter.next();
+            len += 1;
+        }
+        Some((item, len))
+    }
+}
+
+impl<'a, T, D> Iterator for RangeItems<'a, T, D>
+where
+    T: 'a + Item,
+    D: Dimension<Summary = T::Summary>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.end {
+            return None;
+        }
+
+        let leaf = {
+            let &(parent, index) = self.stack.last()?;
+            &parent.children()[index]
+        };
+        self.position = self.position.clone() + &D::from_summary(leaf.summary());
+
+        while let Some((parent, index)) = self.stack.pop() {
+            if let Some(mut tree) = parent.children().get(index + 1) {
+                self.stack.push((parent, index + 1));
+                while let &Node::Internal { ref children, .. } = tree.0.as_ref() {
+                    self.stack.push((tree, 0));
+                    tree = &children[0];
+                }
+                break;
+            }
+        }
+
+        Some(leaf.value())
+    }
+}
+
+impl<'a, T, D> Iterator for RChunksBy<'a, T, D>
+where
+    T: 'a + Item,
+    D: Dimension<Summary = T::Summary> + for<'b> Sub<&'b D, Output = D>,
+{
+    type Item = Tree<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let end = self.end.take()?;
+        let mut chunk_start = D::default();
+        if end > self.budget {
+            let budget_start = end.clone() - &self.budget;
+            self.cursor.seek(&budget_start, SeekBias::Right);
+            chunk_start = self.cursor.start::<D>();
+
+            // Leave an item straddling the budget to the next chunk, unless it is the only item.
+            if chunk_start < budget_start {
+                let item_end = chunk_start.clone()
+                    + &D::from_summary(self.cursor.cur_leaf().unwrap().summary());
+                if item_end < end {
+                    chunk_start = item_end;
+                }
+            }
+        }
+
+        if chunk_start > D::default() {
+            self.cursor.seek(&chunk_start, SeekBias::Right);
+            self.end = Some(chunk_start);
+        } else {
+            self.cursor.reset();
+        }
+        Some(self.cursor.slice(&end, SeekBias::Right))
+    }
+}
+
+impl<'a, T, D> Iterator for WindowsSummaries<'a, T, D>
+where
+    T: 'a + Item,
+    D: Dimension<Summary = T::Summary>,
+{
+    type Item = T::Summary;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.start_cursor.item()?;
+        let window_end = self.start_cursor.start::<D>() + &self.width;
+        if window_end > self.len {
+            return None;
+        }
+
+        while let Some(leaf) = self.end_cursor.cur_leaf() {
+            if self.end_cursor.start::<D>() >= window_end {
+                break;
+            }
+            self.window.push_back(leaf.summary().clone());
+            self.end_cursor.next();
+        }
+
+        let mut summary = T::Summary::default();
+        for item_summary in &self.window {
+            summary += item_summary;
+        }
+        self.window.pop_front();
+        self.start_cursor.next();
+        Some(summary)
+    }
+}
+
+impl<'tree, T: 'tree + Item> Cursor<'tree, T> {
+    fn new(tree: &'tree Tree<T>) -> Self {
+        Self {
+            tree,
+            did_seek: false,
+            stack: Vec::with_capacity(tree.height() as usize),
+            prev_leaf: None,
+            summary: T::Summary::default(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.did_seek = false;
+        self.stack.truncate(0);
+        self.prev_leaf = None;
+        self.summary = T::Summary::default();
+    }
+
+    pub fn start<D: Dimension<Summary = T::Summary>>(&self) -> D {
+        D::from_summary(&self.summary)
+    }
+
+    /// Passes the summary of everything before the cursor to `f`, allowing several dimensions
+    /// to be projected from it at once.
+    pub fn start_many<Out, F: FnOnce(&T::Summary) -> Out>(&self, f: F) -> Out {
+        f(&self.summary)
+    }
+
+    pub fn item<'a>(&'a self) -> Option<&'tree T> {
+        self.cur_leaf().map(|leaf| leaf.value())
+    }
+
+    pub fn prev_item<'a>(&'a self) -> Option<&'tree T> {
+        self.prev_leaf.map(|leaf| leaf.value())
+    }
+
+    /// Returns true if no item lies before the cursor. Unlike the other accessors, this doesn't
+    /// require a prior seek: a fresh cursor is considered to be at the start of the tree.
+    pub fn at_start(&self) -> bool {
+        self.prev_leaf.is_none()
+    }
+
+    /// Returns true if the cursor is parked one past the last item. For an empty tree, the
+    /// cursor is both at the start and at the end.
+    pub fn at_end(&self) -> bool {
+        self.stack.is_empty() && (self.prev_leaf.is_some() || self.tree.is_empty())
+    }
+
+    /// Returns the index of each child the cursor descends into on its way from the root to the
+    /// current item. The path is empty if the cursor is past the end of the tree.
+    pub fn path(&self) -> Vec<usize> {
+        assert!(self.did_seek, "Must seek before reading cursor position");
+        self.stack.iter().map(|&(_, index, _)| index).collect()
+    }
+
+    fn cur_leaf<'a>(&'a self) -> Option<&'tree Tree<T>> {
+        assert!(self.did_seek, "Must seek before reading cursor position");
+        self.stack
+            .last()
+            .map(|&(subtree, index, _)| &subtree.children()[index])
+    }
+
+    pub fn next(&mut self) {
+        assert!(self.did_seek, "Must seek before calling next");
+
+        while self.stack.len() > 0 {
+            let (prev_subtree, index) = {
+                let &mut (prev_subtree, ref mut index, _) = self.stack.last_mut().unwrap();
+                if prev_subtree.height() == 1 {
+                    let prev_leaf = &prev_subtree.children()[*index];
+                    self.prev_leaf = Some(prev_leaf);
+                    self.summary += prev_leaf.summary();
+                }
+                *index += 1;
+                (prev_subtree, *index)
+            };
+            if let Some(child) = prev_subtree.children().get(index) {
+                self.seek_to_first_item(child);
+                break;
+            } else {
+                self.stack.pop();
+            }
+        }
+    }
+
+    pub fn prev(&mut self) {
+        assert!(self.did_seek, "Must seek before calling prev");
+
+        if self.stack.is_empty() && self.prev_leaf.is_some() {
+            self.summary = T::Summary::default();
+            self.seek_to_last_item(self.tree);
+        } else {
+            while self.stack.len() > 0 {
+                let subtree = {
+                    let (parent, index, summary) = self.stack.last_mut().unwrap();
+                    if *index == 0 {
+                        None
+                    } else {
+                        *index -= 1;
+                        self.summary = summary.clone();
+                        for child in &parent.children()[0..*index] {
+                            self.summary += child.summary();
+                        }
+                        parent.children().get(*index)
+                    }
+                };
+                if let Some(subtree) = subtree {
+                    self.seek_to_last_item(subtree);
+                    break;
+                } else {
+                    self.stack.pop();
+                }
+            }
+        }
+
+        self.prev_leaf = if self.stack.is_empty() {
+            None
+        } else {
+            let mut stack_index = self.stack.len() - 1;
+            loop {
+                let (ancestor, index, _) = &self.stack[stack_index];
+                if *index == 0 {
+                    if stack_index == 0 {
+                        break None;
+                    } else {
+                        stack_index -= 1;
+                    }
+                } else {
+                    break ancestor.children()[index - 1].rightmost_leaf();
+                }
+            }
+        };
+    }
+
+    /// Moves the cursor back by `n` units of `D`, clamping at the start of the tree. Rather
+    /// than stepping back one item at a time, this seeks directly to the target position. With
+    /// a dimension that counts items, this moves back by `n` items.
+    pub fn prev_n<D>(&mut self, n: &D)
+    where
+        D: Dimension<Summary = T::Summary> + for<'a> Sub<&'a D, Output = D>,
+    {
+        assert!(self.did_seek, "Must seek before calling prev_n");
+
+        let start = self.start::<D>();
+        let target = if *n >= start { D::default() } else { start - n };
+        self.seek(&target, SeekBias::Right);
+    }
+
+    fn seek_to_first_item<'a>(&'a mut self, mut tree: &'tree Tree<T>) {
+        self.did_seek = true;
+
+        loop {
+            match tree.0.as_ref() {
+                &Node::Internal { ref children, .. } => {
+                    self.stack.push((tree, 0, self.summary.clone()));
+                    tree = &children[0];
+                }
+                &Node::Leaf { .. } => {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn seek_to_last_item<'a>(&'a mut self, mut tree: &'tree Tree<T>) {
+        self.did_seek = true;
+
+        loop {
+            match tree.0.as_ref() {
+                &Node::Internal { ref children, .. } => {
+                    self.stack
+                        .push((tree, children.len() - 1, self.summary.clone()));
+                    for child in &tree.children()[0..children.len() - 1] {
+                        self.summary += child.summary();
+                    }
+                    tree = children.last().unwrap();
+                }
+                &Node::Leaf { .. } => {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Moves the cursor to the item containing `pos`. When `pos` lies on the boundary between two
+    /// items, `bias` decides whether the cursor lands on the item ending there (`Left`) or the one
+    /// starting there (`Right`). Seeking past the end of the tree isn't an error: the cursor lands
+    /// at the end regardless of `bias`. In particular, any seek on an empty tree leaves the cursor
+    /// at its end, where `item` returns `None` and `start` returns zero.
+    pub fn seek<D: Dimension<Summary = T::Summary>>(&mut self, pos: &D, bias: SeekBias) {
+        self.reset();
+        self.seek_and_slice(pos, bias, &mut ());
+    }
+
+    /// Moves the cursor `delta` units of `D` past its current position. Unlike `seek`, this
+    /// continues from the current position instead of descending from the root again.
+    pub fn seek_forward<D: Dimension<Summary = T::Summary>>(&mut self, delta: &D, bias: SeekBias) {
+        let target = self.start::<D>() + delta;
+        self.seek_and_slice(&target, bias, &mut ());
+    }
+
+    /// Moves the cursor to `pos`, landing exactly where `seek` would. The cursor's stack is
+    /// reused rather than reallocated, and when `pos` lies ahead of the current position the
+    /// cursor continues from there instead of descending from the root again.
+    pub fn reset_to<D: Dimension<Summary = T::Summary>>(&mut self, pos: &D, bias: SeekBias) {
+        let start = self.start::<D>();
+        let ahead = *pos > start || (*pos == start && bias == SeekBias::Right);
+        if !(self.did_seek && ahead) {
+            self.reset();
+        }
+        self.seek_and_slice(pos, bias, &mut ());
+    }
+
+    /// Seeks to a position described by a comparator rather than a dimension. `cmp` is passed the
+    /// summary of everything before a candidate position and returns how that position compares
+    /// with the target, which is useful when the target depends on several fields of the summary.
+    /// `cmp` must be monotone, i.e. it must never return `Less` for a position following one for
+    /// which it returned `Equal` or `Greater`. Positions comparing `Equal` are resolved by `bias`
+    /// just like positions equal to a dimension passed to `seek`.
+    pub fn seek_by<F: Fn(&T::Summary) -> Ordering>(&mut self, cmp: F, bias: SeekBias) {
+        self.reset();
+        self.seek_and_slice(&SeekByComparator(cmp), bias, &mut ());
+    }
+
+    /// Moves the cursor like `seek`, continuing from the current position, and returns the items
+    /// it moved past. Like seeking, slicing past the end of the tree returns the remaining items
+    /// and leaves the cursor at the end, so slicing an empty tree always returns an empty tree.
+    pub fn slice<D: Dimension<Summary = T::Summary>>(
+        &mut self,
+        end: &D,
+        bias: SeekBias,
+    ) -> Tree<T> {
+        let mut prefix = Tree::with_config(self.tree.config());
+        self.seek_and_slice(end, bias, &mut prefix);
+        prefix
+    }
+
+    /// Slices like `slice`, but also reports whether `end` coincides with a boundary between two
+    /// items, or with the start or end of the tree. When `end` falls inside an item, that item
+    /// stays behind the cursor for either `bias`, so the slice ends before `end`.
+    pub fn slice_with_boundary<D: Dimension<Summary = T::Summary>>(
+        &mut self,
+        end: &D,
+        bias: SeekBias,
+    ) -> Slice<T> {
+        let tree = self.slice(end, bias);
+        let start = self.start::<D>();
+        let ended_exactly = start == *end
+            || self.cur_leaf().map_or(false, |leaf| {
+                start + &D::from_summary(leaf.summary()) == *end
+            });
+        Slice {
+            tree,
+            ended_exactly,
+        }
+    }
+
+    /// Slices like `slice`, but also returns the summary of the sliced items. The summary is
+    /// accumulated from the subtrees the cursor moves past while the slice is built, so it equals
+    /// the returned tree's `summary()` without reading it off the result.
+    pub fn slice_with_summary<D: Dimension<Summary = T::Summary>>(
+        &mut self,
+        end: &D,
+        bias: SeekBias,
+    ) -> (Tree<T>, T::Summary) {
+        let mut aggregate = (
+            Tree::with_config(self.tree.config()),
+            SummarySeekAggregate(T::Summary::default()),
+        );
+        self.seek_and_slice(end, bias, &mut aggregate);
+        let (slice, summary) = aggregate;
+        (slice, summary.0)
+    }
+
+    /// Moves the cursor forward to `pos` like `slice`, but only returns the summary of the items
+    /// it moved past instead of building a tree out of them.
+    pub fn seek_and_summary<D: Dimension<Summary = T::Summary>>(
+        &mut self,
+        pos: &D,
+        bias: SeekBias,
+    ) -> T::Summary {
+        let mut summary = SummarySeekAggregate(T::Summary::default());
+        self.seek_and_slice(pos, bias, &mut summary);
+        summary.0
+    }
+
+    fn seek_and_slice<Target, Aggregate>(
+        &mut self,
+        pos: &Target,
+        bias: SeekBias,
+        aggregate: &mut Aggregate,
+    ) where
+        Target: SeekTarget<T::Summary>,
+        Aggregate: SeekAggregate<T>,
+    {
+        let mut cur_subtree = None;
+        if self.did_seek {
+            debug_assert!(pos.cmp_end(&self.summary, &T::Summary::default()) != Ordering::Less);
+            while self.stack.len() > 0 {
+                {
+                    let &mut (prev_subtree, ref mut index, _) = self.stack.last_mut().unwrap();
+                    if prev_subtree.height() > 1 {
+                        *index += 1;
+                    }
+
+                    let children_len = prev_subtree.children().len();
+                    while *index < children_len {
+                        let subtree = &prev_subtree.children()[*index];
+                        let summary = subtree.summary();
+                        if bias.skips(pos.cmp_end(&self.summary, summary)) {
+                            self.summary += summary;
+                            self.prev_leaf = subtree.rightmost_leaf();
+                            aggregate.push_subtree(subtree);
+                            *index += 1;
+                        } else {
+                            cur_subtree = Some(subtree);
+                            break;
+                        }
+                    }
+                }
+
+                if cur_subtree.is_some() {
+                    break;
+                } else {
+                    self.stack.pop();
+                }
+            }
+        } else {
+            self.reset();
+            self.did_seek = true;
+            cur_subtree = Some(self.tree);
+        }
+
+        while let Some(subtree) = cur_subtree.take() {
+            match subtree.0.as_ref() {
+                &Node::Internal {
+                    ref rightmost_leaf,
+                    ref summary,
+                    ref children,
+                    ..
+                } => {
+                    if bias.skips(pos.cmp_end(&self.summary, summary)) {
+                        self.summary += summary;
+                        self.prev_leaf = rightmost_leaf.as_ref();
+                        aggregate.push_subtree(subtree);
+                    } else {
+                        let subtree_start = self.summary.clone();
+                        for (index, child) in children.iter().enumerate() {
+                            if bias.skips(pos.cmp_end(&self.summary, child.summary())) {
+                                self.summary += child.summary();
+                                self.prev_leaf = child.rightmost_leaf();
+                                aggregate.push_subtree(child);
+                            } else {
+                                self.stack.push((subtree, index, subtree_start));
+                                cur_subtree = Some(child);
+                                break;
+                            }
+                        }
+                    }
+                }
+                &Node::Leaf { ref summary, .. } => {
+                    // TODO? Can we push the child unconditionally?
+                    if bias.skips(pos.cmp_end(&self.summary, summary)) {
+                        self.prev_leaf = Some(subtree);
+                        self.summary += summary;
+                        aggregate.push_subtree(subtree);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use super::*;
+    use std::cmp;
+
+    #[derive(Default, Eq, PartialEq, Clone, Debug)]
+    pub struct IntegersSummary {
+        count: usize,
+        sum: usize,
+        max: u16,
+    }
+
+    #[derive(Ord, PartialOrd, Default, Eq, PartialEq, Clone, Debug)]
+    struct Count(usize);
+
+    #[derive(Ord, PartialOrd, Default, Eq, PartialEq, Clone, Debug)]
+    struct Sum(usize);
+
+    impl Item for u16 {
+        type Summary = IntegersSummary;
+
+        fn summarize(&self) -> Self::Summary {
+            IntegersSummary {
+                count: 1,
+                sum: *self as usize,
+                max: *self,
+            }
+        }
+    }
+
+    impl<'a> AddAssign<&'a Self> for IntegersSummary {
+        fn add_assign(&mut self, other: &Self) {
+            self.count += other.count;
+            self.sum += other.sum;
+            self.max = cmp::max(self.max, other.max);
+        }
+    }
+
+    impl MaxSummary for IntegersSummary {
+        type Key = u16;
+
+        fn max_key(&self) -> u16 {
+            self.max
+        }
+    }
+
+    impl Dimension for Count {
+        type Summary = IntegersSummary;
+
+        fn from_summary(summary: &Self::Summary) -> Self {
+            Count(summary.count)
+        }
+    }
+
+    impl<'a> Add<&'a Self> for Count {
+        type Output = Self;
+
+        fn add(mut self, other: &Self) -> Self {
+            self.0 += other.0;
+            self
+        }
+    }
+
+    impl<'a> Sub<&'a Self> for Count {
+        type Output = Self;
+
+        fn sub(mut self, other: &Self) -> Self {
+            self.0 -= other.0;
+            self
+        }
+    }
+
+    impl<'a> Sub<&'a Self> for Sum {
+        type Output = Self;
+
+        fn sub(mut self, other: &Self) -> Self {
+            self.0 -= other.0;
+            self
+        }
+    }
+
+    impl Dimension for Sum {
+        type Summary = IntegersSummary;
+
+        fn from_summary(summary: &Self::Summary) -> Self {
+            Sum(summary.sum)
+        }
+    }
+
+    impl<'a> Add<&'a Self> for Sum {
+        type Output = Self;
+
+        fn add(mut self, other: &Self) -> Self {
+            self.0 += other.0;
+            self
+        }
+    }
+
+    impl<T: super::Item> Tree<T> {
+        fn items(&self) -> Vec<T> {
+            self.iter().cloned().collect()
+        }
+    }
+
+    #[test]
+    fn test_extend_and_push() {
+        let mut tree1 = Tree::new();
+        tree1.extend(1..20);
+
+        let mut tree2 = Tree::new();
+        tree2.extend(1..50);
+
+        tree1.push_tree(tree2);
+
+        assert_eq!(tree1.items(), (1..20).chain(1..50).collect::<Vec<u16>>());
+    }
+
+    #[test]
+    fn debug_shape() {
+        fn internal(children: Vec<ShapeNode>) -> ShapeNode {
+            let height = match children.first() {
+                Some(&ShapeNode::Internal { height, .. }) => height + 1,
+                _ => 1,
+            };
+            ShapeNode::Internal { height, children }
+        }
+
+        fn leaves(count: usize) -> ShapeNode {
+            internal(vec![ShapeNode::Leaf; count])
+        }
+
+        assert_eq!(Tree::<u16>::new().debug_shape(), internal(vec![]));
+
+        let mut tree = Tree::new();
+        tree.extend(0..10u16);
+        assert_eq!(
+            tree.debug_shape(),
+            internal(vec![leaves(3), leaves(3), leaves(4)])
+        );
+        assert_eq!(Tree::from_item(0u16).debug_shape(), leaves(1));
+    }
+
+    #[test]
+    fn fill_ratio() {
+        assert_eq!(Tree::<u16>::new().fill_ratio(), 0.0);
+
+        let mut balanced_tree = Tree::new();
+        balanced_tree.extend_balanced(0..1000u16);
+        assert!(balanced_tree.fill_ratio() > 0.9);
+
+        // Pushing items one at a time splits full nodes in half, leaving room in all but the last.
+        let mut pushed_tree = Tree::new();
+        pushed_tree.extend(0..1000u16);
+        assert!(pushed_tree.fill_ratio() < 0.8);
+
+        // Removing every other item stitches the tree back together out of many small slices.
+        let mut sparse_tree = balanced_tree.clone();
+        sparse_tree.bulk_remove_sorted(&(0..500).map(|i| Count(2 * i)).collect::<Vec<_>>());
+        assert!(sparse_tree.fill_ratio() < balanced_tree.fill_ratio());
+
+        for leaf in balanced_tree.leaf_trees().take(3) {
+            assert_eq!(leaf.fill_ratio(), 1.0);
+        }
+    }
+
+    #[test]
+    fn boundary_height() {
+        assert_eq!(Tree::<u16>::new().boundary_height(&Count(0)), None);
+
+        // Leaves are grouped as [3, 3, 4] under the root.
+        let mut tree = Tree::new();
+        tree.extend(1..=10u16);
+        for &(pos, height) in &[
+            (0, Some(2)),
+            (1, Some(0)),
+            (3, Some(1)),
+            (5, Some(0)),
+            (6, Some(1)),
+            (9, Some(0)),
+            (10, None),
+            (11, None),
+        ] {
+            assert_eq!(tree.boundary_height(&Count(pos)), height, "pos: {}", pos);
+        }
+
+        assert_eq!(tree.boundary_height(&Sum(6)), Some(1));
+        assert_eq!(tree.boundary_height(&Sum(10)), Some(0));
+        assert_eq!(tree.boundary_height(&Sum(7)), None);
+        assert_eq!(tree.boundary_height(&Sum(55)), None);
+    }
+
+    #[test]
+    fn custom_fanout() {
+        let mut tree = Tree::builder().min_children(8).max_children(16).build();
+        tree.extend(0..1000u16);
+        assert_eq!(tree.items(), (0..1000).collect::<Vec<_>>());
+        assert_fanout(&tree, 8, 16);
+
+        let mut suffix = tree.split_off(&Count(300));
+        suffix.splice(&Count(100)..&Count(200), 2000..2500);
+        tree.append(&mut suffix);
+        let mut expected = (0..400).collect::<Vec<_>>();
+        expected.extend(2000..2500);
+        expected.extend(500..1000);
+        assert_eq!(tree.items(), expected);
+        assert_fanout(&tree, 8, 16);
+        assert_eq!(suffix.config(), tree.config());
+    }
+
+    #[test]
+    #[should_panic(expected = "max_children must be at least 2 * min_children - 1")]
+    fn invalid_fanout() {
+        Tree::<u16>::builder()
+            .min_children(8)
+            .max_children(14)
+            .build();
+    }
+
+    #[test]
+    #[should_panic(expected = "max_children must be at least 2")]
+    fn unary_fanout() {
+        Tree::<u16>::builder()
+            .min_children(1)
+            .max_children(1)
+            .build();
+    }
+
+    fn assert_fanout<T: Item>(tree: &Tree<T>, min_children: usize, max_children: usize) {
+        assert_eq!(tree.config().min_children, min_children);
+        assert_eq!(tree.config().max_children, max_children);
+        let mut stack = vec![tree];
+        while let Some(subtree) = stack.pop() {
+            if subtree.height() > 0 {
+                assert!(subtree.children().len() <= max_children);
+                if !Arc::ptr_eq(&subtree.0, &tree.0) {
+                    assert!(subtree.children().len() >= min_children);
+                }
+                stack.extend(subtree.children());
+            }
+        }
+    }
+
+    #[test]
+    fn extend_balanced() {
+        for prefix_len in 0..20 {
+            for batch_len in (0..50).chain(Some(1000)) {
+                let mut tree1 = Tree::new();
+                tree1.extend(0..prefix_len);
+                let mut tree2 = tree1.clone();
+                tree1.extend(100..100 + batch_len);
+                tree2.extend_balanced(100..100 + batch_len);
+                assert_eq!(tree1.items(), tree2.items());
+                assert_eq!(tree1.summary(), tree2.summary());
+                assert_fanout(&tree2, MIN_CHILDREN, MAX_CHILDREN);
+            }
+        }
+
+        let mut tree = Tree::builder().min_children(8).max_children(16).build();
+        tree.extend_balanced(0..1000u16);
+        assert_eq!(tree.items(), (0..1000).collect::<Vec<_>>());
+        assert_fanout(&tree, 8, 16);
+    }
+
+    #[test]
+    fn extend_front() {
+        let mut tree = Tree::new();
+        tree.extend(vec![4, 5]);
+        tree.extend_front(vec![1, 2, 3]);
+        assert_eq!(tree.items(), vec![1, 2, 3, 4, 5]);
+
+        for suffix_len in 0..20 {
+            for batch_len in (0..50).chain(Some(1000)) {
+                let mut tree = Tree::new();
+                tree.extend(1000..1000 + suffix_len);
+                tree.extend_front(0..batch_len);
+                assert_eq!(
+                    tree.items(),
+                    (0..batch_len)
+                        .chain(1000..1000 + suffix_len)
+                        .collect::<Vec<_>>()
+                );
+                assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+            }
+        }
+
+        let mut tree = Tree::builder().min_children(8).max_children(16).build();
+        tree.extend(500..1000u16);
+        tree.extend_front(0..500);
+        assert_eq!(tree.items(), (0..1000).collect::<Vec<_>>());
+        assert_fanout(&tree, 8, 16);
+    }
+
+    #[test]
+    fn try_extend_balanced() {
+        #[derive(Clone, Eq, PartialEq, Debug)]
+        struct Byte(u8);
+
+        #[derive(Clone, Default, Eq, PartialEq, Debug)]
+        struct ByteSum(u8);
+
+        impl<'a> AddAssign<&'a Self> for ByteSum {
+            fn add_assign(&mut self, other: &Self) {
+                self.0 = self.0.wrapping_add(other.0);
+            }
+        }
+
+        impl Item for Byte {
+            type Summary = ByteSum;
+
+            fn summarize(&self) -> Self::Summary {
+                ByteSum(self.0)
+            }
+
+            fn checked_add_summary(
+                summary: &mut Self::Summary,
+                other: &Self::Summary,
+            ) -> Result<(), SummaryOverflow> {
+                summary.0 = summary.0.checked_add(other.0).ok_or(SummaryOverflow)?;
+                Ok(())
+            }
+        }
+
+        assert_eq!(
+            Tree::try_build_balanced((0..30).map(Byte), TreeConfig::default()).map(|_| ()),
+            Err(SummaryOverflow)
+        );
+
+        let mut tree = Tree::new();
+        assert_eq!(tree.try_extend_balanced((0..20).map(Byte)), Ok(()));
+        assert_eq!(tree.summary(), &ByteSum(190));
+        assert_eq!(
+            tree.try_extend_balanced((30..40).map(Byte)),
+            Err(SummaryOverflow)
+        );
+        assert_eq!(
+            tree.try_extend_balanced(vec![Byte(70)]),
+            Err(SummaryOverflow)
+        );
+        assert_eq!(tree.summary(), &ByteSum(190));
+        assert_eq!(tree.iter().count(), 20);
+        assert_eq!(tree.try_extend_balanced(vec![Byte(65)]), Ok(()));
+        assert_eq!(tree.summary(), &ByteSum(255));
+    }
+
+    #[test]
+    fn zero_sized_items() {
+        use super::scalars::ItemCount;
+
+        #[derive(Clone, Eq, PartialEq, Debug)]
+        struct Marker;
+
+        impl Item for Marker {
+            type Summary = usize;
+
+            fn summarize(&self) -> Self::Summary {
+                1
+            }
+        }
+
+        let mut tree = Tree::new();
+        tree.extend_balanced((0..1000).map(|_| Marker));
+        assert_eq!(tree.len::<ItemCount>(), ItemCount(1000));
+        assert!(tree.summary_is_consistent());
+        assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+        let first_leaf = tree.leaf_trees().next().unwrap().clone();
+        assert!(tree
+            .leaf_trees()
+            .all(|leaf| Arc::ptr_eq(&leaf.0, &first_leaf.0)));
+
+        {
+            let mut cursor = tree.cursor();
+            cursor.seek(&ItemCount(500), SeekBias::Right);
+            assert_eq!(cursor.start::<ItemCount>(), ItemCount(500));
+            assert_eq!(cursor.item(), Some(&Marker));
+            cursor.seek(&ItemCount(1000), SeekBias::Left);
+            assert_eq!(cursor.start::<ItemCount>(), ItemCount(999));
+            cursor.seek(&ItemCount(1000), SeekBias::Right);
+            assert_eq!(cursor.item(), None);
+        }
+
+        tree.splice(&ItemCount(100)..&ItemCount(200), vec![Marker; 5]);
+        tree.push(Marker);
+        assert_eq!(tree.len::<ItemCount>(), ItemCount(906));
+        assert_eq!(
+            tree.get_range_as_vec(&ItemCount(10)..&ItemCount(20)),
+            vec![Marker; 10]
+        );
+        assert_eq!(tree.iter().count(), 906);
+        assert!(tree.summary_is_consistent());
+    }
+
+    #[test]
+    fn concat_all() {
+        assert!(Tree::<u16>::concat_all(Vec::new()).is_empty());
+
+        let mut parts = Vec::new();
+        let mut items = Vec::new();
+        for &len in &[64, 3, 200, 0, 17, 64] {
+            let mut part = Tree::new();
+            part.extend(items.len() as u16..(items.len() + len) as u16);
+            items.extend(part.items());
+            parts.push(part);
+        }
+
+        let tree = Tree::concat_all(parts.clone());
+        assert_eq!(tree.items(), items);
+        assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+
+        let is_shared = |subtree: &Tree<u16>| {
+            (0..tree.height() + 1).any(|height| {
+                tree.subtrees_at_height(height)
+                    .any(|tree_subtree| Arc::ptr_eq(&tree_subtree.0, &subtree.0))
+            })
+        };
+        for part in &parts {
+            for part_subtree in part.subtrees_at_height(1) {
+                assert!(is_shared(part_subtree));
+            }
+        }
+    }
+
+    #[test]
+    fn try_map() {
+        use std::cell::Cell;
+
+        let mut tree = Tree::new();
+        tree.extend(0..100u16);
+        let doubled = tree.try_map(|&item| Ok::<_, ()>(item * 2)).unwrap();
+        assert_eq!(
+            doubled.items(),
+            tree.iter().map(|&item| item * 2).collect::<Vec<_>>()
+        );
+        assert_fanout(&doubled, MIN_CHILDREN, MAX_CHILDREN);
+
+        let calls = Cell::new(0);
+        let result = tree.try_map(|&item| {
+            calls.set(calls.get() + 1);
+            if item % 30 == 29 {
+                Err(item)
+            } else {
+                Ok(item)
+            }
+        });
+        assert_eq!(result, Err(29));
+        assert_eq!(calls.get(), 30);
+    }
+
+    #[test]
+    fn scan_into() {
+        let mut tree = Tree::new();
+        tree.extend(vec![1, 1, 1u16]);
+        let running_sums = tree.scan_into(0, |sum, &delta| {
+            *sum += delta;
+            *sum
+        });
+        assert_eq!(running_sums.items(), vec![1, 2, 3]);
+
+        let mut tree = Tree::new();
+        tree.extend((0..500u16).map(|i| i % 3));
+        let running_sums: Tree<u16> = tree.scan_into(0, |sum, &delta| {
+            *sum += delta;
+            *sum
+        });
+        let mut sum = 0;
+        let expected_sums = tree
+            .iter()
+            .map(|delta| {
+                sum += delta;
+                sum
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(running_sums.items(), expected_sums);
+        assert_fanout(&running_sums, MIN_CHILDREN, MAX_CHILDREN);
+        assert!(running_sums.summary_is_consistent());
+        assert!(Tree::<u16>::new().scan_into(0, |_, &item| item).is_empty());
+    }
+
+    #[test]
+    fn interleave() {
+        let mut odd = Tree::new();
+        odd.extend(vec![1, 3, 5u16]);
+        let mut even = Tree::new();
+        even.extend(vec![2, 4u16]);
+        assert_eq!(odd.interleave(&even).items(), [1, 2, 3, 4, 5]);
+        assert_eq!(even.interleave(&odd).items(), [2, 1, 4, 3, 5]);
+        assert_eq!(odd.interleave(&Tree::new()).items(), [1, 3, 5]);
+        assert_eq!(Tree::new().interleave(&even).items(), [2, 4]);
+
+        let mut long = Tree::new();
+        long.extend(10..100u16);
+        let interleaved = even.interleave(&long);
+        assert_eq!(
+            interleaved.items(),
+            [2, 10, 4]
+                .iter()
+                .cloned()
+                .chain(11..100)
+                .collect::<Vec<_>>()
+        );
+        assert_fanout(&interleaved, MIN_CHILDREN, MAX_CHILDREN);
+    }
+
+    #[test]
+    fn zip_map() {
+        let mut a = Tree::new();
+        a.extend(0..100u16);
+        let mut b = Tree::new();
+        b.extend((0..100u16).map(|item| item * 2));
+        let sum = a.zip_map(&b, |a, b| a + b);
+        assert_eq!(
+            sum.items(),
+            (0..100).map(|item| item * 3).collect::<Vec<_>>()
+        );
+        assert_fanout(&sum, MIN_CHILDREN, MAX_CHILDREN);
+
+        let empty = Tree::<u16>::new();
+        assert!(empty.zip_map(&empty, |a, b| a + b).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Tried to zip trees with different numbers of items")]
+    fn zip_map_length_mismatch() {
+        let mut a = Tree::new();
+        a.extend(0..10u16);
+        let mut b = Tree::new();
+        b.extend(0..9u16);
+        a.zip_map(&b, |a, b| a + b);
+    }
+
+    #[test]
+    fn repeat_and_resize() {
+        let tree = Tree::repeat(7u16, 100);
+        assert_eq!(tree.items(), vec![7; 100]);
+        assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+        assert_eq!(Tree::repeat(7u16, 0), Tree::new());
+
+        let mut tree = Tree::new();
+        tree.extend(1..=5u16);
+        tree.resize(Count(12), 0);
+        assert_eq!(tree.items(), [1, 2, 3, 4, 5, 0, 0, 0, 0, 0, 0, 0]);
+        assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+        tree.resize(Count(12), 9);
+        assert_eq!(tree.len::<Count>(), Count(12));
+        tree.resize(Count(3), 0);
+        assert_eq!(tree.items(), [1, 2, 3]);
+        tree.resize(Count(0), 0);
+        assert_eq!(tree.items(), Vec::<u16>::new());
+
+        // Filling along another dimension overshoots rather than stopping short.
+        tree.resize(Sum(5), 2);
+        assert_eq!(tree.items(), [2, 2, 2]);
+    }
+
+    #[test]
+    fn fill() {
+        let tree = Tree::fill(Count(10), 3u16);
+        assert_eq!(tree.items(), vec![3; 10]);
+        assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+        assert_eq!(Tree::fill(Count(0), 3u16), Tree::new());
+        assert_eq!(Tree::fill(Sum(9), 3u16).items(), [3, 3, 3]);
+        assert_eq!(Tree::fill(Sum(10), 3u16).items(), [3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn split_every() {
+        assert_eq!(
+            Tree::<u16>::new().split_every(Count(3)),
+            Vec::<Tree<u16>>::new()
+        );
+
+        for len in 1..50 {
+            let mut tree = Tree::new();
+            tree.extend(0..len);
+            let pieces = tree.split_every(Count(4));
+            assert_eq!(pieces.len(), (len as usize + 3) / 4);
+
+            let mut concatenation = Tree::new();
+            for (index, piece) in pieces.iter().enumerate() {
+                if index < pieces.len() - 1 {
+                    assert_eq!(piece.len::<Count>(), Count(4));
+                } else {
+                    assert!(piece.len::<Count>() <= Count(4));
+                }
+                concatenation.push_tree(piece.clone());
+            }
+            assert_eq!(concatenation.items(), tree.items());
+        }
+
+        let mut tree = Tree::new();
+        tree.extend(vec![1, 1, 5, 1]);
+        let pieces = tree
+            .split_every(Sum(2))
+            .iter()
+            .map(|piece| piece.items())
+            .collect::<Vec<_>>();
+        assert_eq!(pieces, vec![vec![1, 1], vec![], vec![], vec![5, 1]]);
+    }
+
+    #[test]
+    fn rotate() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        for seed in 0..20 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let len = rng.gen_range(0, 100);
+            let mut tree = Tree::new();
+            tree.extend(rng.gen_iter::<u16>().take(len));
+            let mut reference = tree.items();
+
+            for _ in 0..10 {
+                let mid = match rng.gen_range(0, 4) {
+                    0 => 0,
+                    1 => len,
+                    _ => rng.gen_range(0, len + 1),
+                };
+                if rng.gen() {
+                    tree.rotate_left(&Count(mid));
+                    reference.rotate_left(mid);
+                } else {
+                    tree.rotate_right(&Count(mid));
+                    reference.rotate_right(mid);
+                }
+                assert_eq!(tree.items(), reference);
+            }
+        }
+    }
+
+    #[test]
+    fn serialization() {
+        use serde_json;
+
+        let mut tree = Tree::new();
+        tree.extend(0..100u16);
+        let json = serde_json::to_string(&tree).unwrap();
+        let items = serde_json::from_str::<Vec<u16>>(&json).unwrap();
+        assert_eq!(items, tree.items());
+
+        let mut expected_tree = Tree::new();
+        expected_tree.extend(items);
+        assert_eq!(
+            serde_json::from_str::<Tree<u16>>(&json).unwrap(),
+            expected_tree
+        );
+
+        assert_eq!(serde_json::to_string(&Tree::<u16>::new()).unwrap(), "[]");
+        assert_eq!(
+            serde_json::from_str::<Tree<u16>>("[]").unwrap(),
+            Tree::new()
+        );
+        assert!(serde_json::from_str::<Tree<u16>>("[1, -1]").is_err());
+    }
+
+    #[test]
+    fn extend_from_slice() {
+        let items: &[u16] = &[5, 4, 3, 2, 1];
+        let mut tree = Tree::new();
+        tree.extend(items.iter());
+        tree.extend(&items[1..3]);
+        assert_eq!(tree.items(), vec![5, 4, 3, 2, 1, 4, 3]);
+    }
+
+    #[test]
+    fn single() {
+        assert_eq!(Tree::<u16>::new().single(), None);
+        assert_eq!(Tree::from_item(3u16).single(), Some(&3));
+
+        let mut tree = Tree::new();
+        tree.extend(0..2u16);
+        assert_eq!(tree.single(), None);
+
+        tree.extend(2..100u16);
+        tree.splice(&Count(1)..&Count(100), None);
+        assert_eq!(tree.single(), Some(&0));
+        tree.splice(&Count(0)..&Count(1), None);
+        assert_eq!(tree.single(), None);
+    }
+
+    #[test]
+    fn first_and_last_summary() {
+        let tree = Tree::<u16>::new();
+        assert_eq!(tree.first(), None);
+        assert_eq!(tree.first_summary(), None);
+        assert_eq!(tree.last_summary(), None);
+
+        let tree = Tree::from_item(5u16);
+        assert_eq!(tree.first_summary(), Some(&5.summarize()));
+        assert_eq!(tree.last_summary(), Some(&5.summarize()));
+
+        let mut tree = Tree::new();
+        tree.extend(3..100u16);
+        assert_eq!(tree.first(), Some(&3));
+        assert_eq!(
+            tree.first_summary(),
+            Some(&tree.first().unwrap().summarize())
+        );
+        assert_eq!(tree.last_summary(), Some(&tree.last().unwrap().summarize()));
+
+        let suffix = tree.split_off(&Count(50));
+        assert_eq!(suffix.first_summary(), Some(&53.summarize()));
+        assert_eq!(tree.last_summary(), Some(&52.summarize()));
+    }
+
+    #[test]
+    fn collect_into() {
+        let mut tree = Tree::new();
+        tree.extend(0..100u16);
+        let mut items = vec![1000, 1001];
+        tree.collect_into(&mut items);
+        assert_eq!(items, tree.iter().cloned().collect::<Vec<_>>());
+
+        let capacity = items.capacity();
+        for _ in 0..10 {
+            tree.collect_into(&mut items);
+            assert_eq!(items.len(), 100);
+            assert_eq!(items.capacity(), capacity);
+        }
+
+        Tree::new().collect_into(&mut items);
+        assert!(items.is_empty());
+        assert_eq!(items.capacity(), capacity);
+    }
+
+    #[test]
+    fn iter_peek() {
+        let mut tree = Tree::new();
+        tree.extend(1..=20u16);
+        let mut iter = tree.iter();
+        assert_eq!(iter.peek(), Some(&1));
+        assert_eq!(iter.peek(), Some(&1));
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next(), Some(&2));
+
+        let mut items = Vec::new();
+        while let Some(&item) = iter.peek() {
+            assert_eq!(iter.next(), Some(&item));
+            items.push(item);
+        }
+        assert_eq!(items, (3..=20).collect::<Vec<_>>());
+        assert_eq!(iter.peek(), None);
+        assert_eq!(iter.next(), None);
+        assert_eq!(Tree::<u16>::new().iter().peek(), None);
+    }
+
+    #[test]
+    fn rev_iter() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        assert_eq!(Tree::<u16>::new().rev_iter().next(), None);
+        for seed in 0..20 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let mut tree = Tree::new();
+            let items_count = rng.gen_range(0, 1000);
+            tree.extend(rng.gen_iter::<u16>().take(items_count));
+
+            let mut expected_items = tree.items();
+            expected_items.reverse();
+            assert_eq!(tree.rev_iter().cloned().collect::<Vec<_>>(), expected_items);
+        }
+    }
+
+    #[test]
+    fn iter_summaries() {
+        let tree = Tree::<u16>::new();
+        assert_eq!(tree.iter_summaries().count(), 0);
+
+        let mut tree = Tree::new();
+        tree.extend(1..=10u16);
+        let sums = tree
+            .iter_summaries()
+            .map(|(item, summary)| (*item, summary.sum))
+            .collect::<Vec<_>>();
+        assert_eq!(&sums[..3], &[(1, 1), (2, 3), (3, 6)]);
+        assert_eq!(sums.last(), Some(&(10, 55)));
+        assert_eq!(
+            tree.iter_summaries().last().unwrap().1,
+            tree.total_summary()
+        );
+    }
+
+    #[test]
+    fn iter_summaries_while() {
+        let mut tree = Tree::new();
+        tree.extend(1..=100u16);
+
+        let mut visited = Vec::new();
+        let summary = tree.iter_summaries_while(|item, summary| {
+            visited.push(*item);
+            summary.sum <= 20
+        });
+        assert_eq!(visited.last(), Some(&6));
+        assert_eq!(summary.count, 5);
+        assert_eq!(summary.sum, 15);
+
+        let mut visited = Vec::new();
+        let summary = tree.iter_summaries_while(|item, summary| {
+            visited.push(*item);
+            summary.count <= 90
+        });
+        assert_eq!(visited.last(), Some(&91));
+        assert!(visited.len() < 40);
+        assert_eq!(summary.count, 90);
+        assert_eq!(summary.sum, 4095);
+
+        assert_eq!(tree.iter_summaries_while(|_, _| true), tree.total_summary());
+        assert_eq!(
+            tree.iter_summaries_while(|_, _| false),
+            IntegersSummary::default()
+        );
+    }
+
+    #[test]
+    fn fold_items() {
+        let mut tree = Tree::new();
+        tree.extend(1..=10u16);
+
+        let mut visited = Vec::new();
+        let partial_sum = tree.fold_items(0, |sum, &item| {
+            visited.push(item);
+            if sum + item > 20 {
+                ControlFlow::Break(sum)
+            } else {
+                ControlFlow::Continue(sum + item)
+            }
+        });
+        assert_eq!(partial_sum, 15);
+        assert_eq!(visited, vec![1, 2, 3, 4, 5, 6]);
+
+        let sum = tree.fold_items(0, |sum, &item| ControlFlow::Continue(sum + item));
+        assert_eq!(sum, 55);
+        assert_eq!(
+            Tree::<u16>::new().fold_items(7, |_, _| ControlFlow::Break(0)),
+            7
+        );
+    }
+
+    #[test]
+    fn windows_summaries() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        let mut tree = Tree::new();
+        tree.extend(1..=6u16);
+        let sums = tree
+            .windows_summaries(Count(3))
+            .map(|summary| summary.sum)
+            .collect::<Vec<_>>();
+        assert_eq!(sums, vec![6, 9, 12, 15]);
+        assert_eq!(tree.windows_summaries(Count(6)).count(), 1);
+        assert_eq!(tree.windows_summaries(Count(7)).count(), 0);
+        assert_eq!(Tree::<u16>::new().windows_summaries(Count(1)).count(), 0);
+
+        // The items starting at sums 0, 1, 3 and 6 fall into windows of width 4 as
+        // [1, 2, 3], [2, 3], [3, 4] and [4].
+        let mut tree = Tree::new();
+        tree.extend(1..=4u16);
+        let sums = tree
+            .windows_summaries(Sum(4))
+            .map(|summary| summary.sum)
+            .collect::<Vec<_>>();
+        assert_eq!(sums, vec![6, 5, 7, 4]);
+
+        let mut rng = StdRng::from_seed(&[5]);
+        let mut tree = Tree::new();
+        tree.extend(rng.gen_iter::<u16>().take(200));
+        let items = tree.items();
+        let expected_sums = items
+            .windows(10)
+            .map(|window| window.iter().map(|item| *item as usize).sum())
+            .collect::<Vec<usize>>();
+        let sums = tree
+            .windows_summaries(Count(10))
+            .map(|summary| summary.sum)
+            .collect::<Vec<_>>();
+        assert_eq!(sums, expected_sums);
+    }
+
+    #[test]
+    fn cursor_reuse_does_not_reallocate() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        let mut rng = StdRng::from_seed(&[7]);
+        let mut tree = Tree::new();
+        tree.extend(rng.gen_iter::<u16>().take(1000));
+        let len = tree.len::<Count>().0;
+
+        let mut cursor = tree.cursor();
+        cursor.seek(&Count(len / 2), SeekBias::Left);
+        let capacity = cursor.stack.capacity();
+        assert!(capacity >= tree.height() as usize);
+        for _ in 0..5000 {
+            let start = rng.gen_range(0, len + 1);
+            let bias = if rng.gen() {
+                SeekBias::Left
+            } else {
+                SeekBias::Right
+            };
+            cursor.seek(&Count(start), bias);
+            if rng.gen() {
+                let end = rng.gen_range(start, len + 1);
+                cursor.slice(&Count(end), bias);
+            } else if rng.gen() {
+                cursor.prev();
+            } else {
+                cursor.next();
+            }
+            assert_eq!(cursor.stack.capacity(), capacity);
+        }
+    }
+
+    #[test]
+    fn slice_with_boundary() {
+        let mut tree = Tree::new();
+        tree.extend(1..=5u16);
+        let mut cursor = tree.cursor();
+
+        for &(end, bias, ref items, ended_exactly) in &[
+            (0, SeekBias::Right, vec![], true),
+            (3, SeekBias::Right, vec![1, 2], true),
+            (4, SeekBias::Right, vec![1, 2], false),
+            (3, SeekBias::Left, vec![1], true),
+            (4, SeekBias::Left, vec![1, 2], false),
+            (15, SeekBias::Right, vec![1, 2, 3, 4, 5], true),
+            (15, SeekBias::Left, vec![1, 2, 3, 4], true),
+            (16, SeekBias::Right, vec![1, 2, 3, 4, 5], false),
+        ] {
+            cursor.reset();
+            let slice = cursor.slice_with_boundary(&Sum(end), bias);
+            assert_eq!(&slice.tree.items(), items);
+            assert_eq!(
+                slice.ended_exactly, ended_exactly,
+                "end: {}, bias: {:?}",
+                end, bias
+            );
+        }
+
+        let mut tree = Tree::new();
+        tree.extend(vec![2, 2, 2u16]);
+        for &bias in &[SeekBias::Left, SeekBias::Right] {
+            let mut cursor = tree.cursor();
+            cursor.reset();
+            let slice = cursor.slice_with_boundary(&Sum(3), bias);
+            assert_eq!(slice.tree.items(), [2]);
+            assert!(!slice.ended_exactly);
+            assert_eq!(cursor.start::<Sum>(), Sum(2));
+        }
+    }
+
+    #[test]
+    fn slice_with_summary() {
+        let mut tree = Tree::new();
+        tree.extend(1..=20u16);
+        let mut cursor = tree.cursor();
+        cursor.seek(&Count(3), SeekBias::Right);
+
+        let (slice, summary) = cursor.slice_with_summary(&Count(8), SeekBias::Right);
+        assert_eq!(slice.items(), vec![4, 5, 6, 7, 8]);
+        assert_eq!(&summary, slice.summary());
+        assert_eq!(summary.sum, 30);
+
+        let (slice, summary) = cursor.slice_with_summary(&Sum(100), SeekBias::Left);
+        assert_eq!(&summary, slice.summary());
+        assert_eq!(summary.count, 5);
+
+        let (slice, summary) = cursor.slice_with_summary(&Count(30), SeekBias::Right);
+        assert_eq!(slice.items(), (14..=20).collect::<Vec<_>>());
+        assert_eq!(&summary, slice.summary());
+    }
+
+    #[test]
+    fn count_matching() {
+        use std::cell::Cell;
+
+        let calls = Cell::new(0);
+        let count_above_100 = |summary: &IntegersSummary| {
+            calls.set(calls.get() + 1);
+            if summary.max <= 100 {
+                Some(0)
+            } else if summary.count == 1 {
+                Some(1)
+            } else {
+                None
+            }
+        };
+
+        assert_eq!(Tree::<u16>::new().count_matching(&count_above_100), 0);
+
+        let mut tree = Tree::new();
+        tree.extend((0..1000).map(|index| {
+            if index % 250 == 7 {
+                500
+            } else {
+                index as u16 % 100
+            }
+        }));
+        calls.set(0);
+        assert_eq!(tree.count_matching(&count_above_100), 4);
+        assert!(calls.get() < 200);
+
+        let mut tree = Tree::new();
+        tree.extend((0..300).map(|index| index as u16));
+        assert_eq!(tree.count_matching(&count_above_100), 199);
+    }
+
+    #[test]
+    fn seek_and_summary() {
+        let mut tree = Tree::new();
+        tree.extend(1..=6u16);
+        let mut prefix = Tree::new();
+        prefix.extend(1..=3u16);
+        let mut middle = Tree::new();
+        middle.extend(4..=5u16);
+
+        let mut cursor = tree.cursor();
+        assert_eq!(
+            cursor.seek_and_summary(&Count(3), SeekBias::Right),
+            prefix.total_summary()
+        );
+        assert_eq!(cursor.item(), Some(&4));
+        assert_eq!(
+            cursor.seek_and_summary(&Sum(15), SeekBias::Right),
+            middle.total_summary()
+        );
+        assert_eq!(cursor.item(), Some(&6));
+
+        let mut slice_cursor = tree.cursor();
+        let mut summary_cursor = tree.cursor();
+        for end in 0..tree.len::<Sum>().0 + 1 {
+            for &bias in &[SeekBias::Left, SeekBias::Right] {
+                slice_cursor.seek(&Count(0), SeekBias::Right);
+                summary_cursor.seek(&Count(0), SeekBias::Right);
+                assert_eq!(
+                    summary_cursor.seek_and_summary(&Sum(end), bias),
+                    slice_cursor.slice(&Sum(end), bias).total_summary()
+                );
+                assert_eq!(
+                    summary_cursor.start::<Count>(),
+                    slice_cursor.start::<Count>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn seek_forward() {
+        let mut tree = Tree::new();
+        tree.extend(1..=30u16);
+
+        let mut cursor = tree.cursor();
+        let mut next_cursor = tree.cursor();
+        cursor.seek(&Count(0), SeekBias::Right);
+        next_cursor.seek(&Count(0), SeekBias::Right);
+        let mut items = Vec::new();
+        while let Some(item) = cursor.item() {
+            assert_eq!(next_cursor.item(), Some(item));
+            assert_eq!(next_cursor.start::<Sum>(), cursor.start::<Sum>());
+            items.push(*item);
+            cursor.seek_forward(&Count(1), SeekBias::Right);
+            next_cursor.next();
+        }
+        assert_eq!(next_cursor.item(), None);
+        assert_eq!(items, tree.items());
+
+        let mut cursor = tree.cursor();
+        cursor.seek(&Sum(10), SeekBias::Right);
+        assert_eq!(cursor.item(), Some(&5));
+        cursor.seek_forward(&Sum(5), SeekBias::Right);
+        assert_eq!(cursor.item(), Some(&6));
+        assert_eq!(cursor.start::<Sum>(), Sum(15));
+        cursor.seek_forward(&Sum(5), SeekBias::Left);
+        assert_eq!(cursor.item(), Some(&6));
+        cursor.seek_forward(&Sum(6), SeekBias::Right);
+        assert_eq!(cursor.item(), Some(&7));
+    }
+
+    #[test]
+    fn reset_to() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        for seed in 0..20 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let mut tree = Tree::new();
+            let items_count = rng.gen_range(0, 100);
+            tree.extend((0..items_count).map(|_| rng.gen_range(0, 4)));
+            let sum = tree.len::<Sum>().0;
+
+            let mut cursor = tree.cursor();
+            for _ in 0..50 {
+                let pos = Sum(rng.gen_range(0, sum + 2));
+                let bias = if rng.gen() {
+                    SeekBias::Left
+                } else {
+                    SeekBias::Right
+                };
+                cursor.reset_to(&pos, bias);
+
+                let mut fresh_cursor = tree.cursor();
+                fresh_cursor.seek(&pos, bias);
+                assert_eq!(cursor.item(), fresh_cursor.item());
+                assert_eq!(cursor.prev_item(), fresh_cursor.prev_item());
+                assert_eq!(cursor.start::<Count>(), fresh_cursor.start::<Count>());
+                assert_eq!(cursor.start::<Sum>(), fresh_cursor.start::<Sum>());
+            }
+        }
+    }
+
+    #[test]
+    fn seek_by() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        let mut rng = StdRng::from_seed(&[3]);
+        let mut tree = Tree::new();
+        tree.extend(rng.gen_iter::<u16>().map(|item| item % 10).take(100));
+        let len = tree.len::<Sum>().0;
+
+        let mut cursor1 = tree.cursor();
+        let mut cursor2 = tree.cursor();
+        for target in 0..len + 2 {
+            for &bias in &[SeekBias::Left, SeekBias::Right] {
+                cursor1.seek(&Sum(target), bias);
+                cursor2.seek_by(|summary| summary.sum.cmp(&target), bias);
+                assert_eq!(cursor1.item(), cursor2.item());
+                assert_eq!(cursor1.start::<Count>(), cursor2.start::<Count>());
+                assert_eq!(cursor1.start::<Sum>(), cursor2.start::<Sum>());
+            }
+        }
+
+        // Combine two fields of the summary into a single monotone position.
+        let target = len / 2;
+        cursor2.seek_by(
+            |summary| (summary.sum + summary.count).cmp(&target),
+            SeekBias::Right,
+        );
+        let items = tree.items();
+        let expected_count = (0..items.len() + 1)
+            .filter(|&count| {
+                let sum = items[..count]
+                    .iter()
+                    .map(|&item| item as usize)
+                    .sum::<usize>();
+                sum + count <= target
+            })
+            .last()
+            .unwrap();
+        assert_eq!(cursor2.start::<Count>(), Count(expected_count));
+    }
+
+    #[test]
+    fn subtrees_at_height() {
+        assert_eq!(Tree::<u16>::new().subtrees_at_height(0).count(), 0);
+        assert_eq!(Tree::<u16>::new().subtrees_at_height(1).count(), 0);
+
+        let mut tree = Tree::new();
+        tree.extend(0..100u16);
+        for height in 0..tree.height() + 1 {
+            let mut concatenation = Tree::new();
+            for subtree in tree.subtrees_at_height(height) {
+                assert_eq!(subtree.height(), height);
+                concatenation.push_tree(subtree.clone());
+            }
+            assert_eq!(concatenation.items(), tree.items());
+        }
+        assert_eq!(tree.subtrees_at_height(0).count(), 100);
+        assert_eq!(tree.subtrees_at_height(tree.height()).count(), 1);
+        assert_eq!(tree.subtrees_at_height(tree.height() + 1).count(), 0);
+
+        let leaves = tree
+            .subtrees_at_height(0)
+            .flat_map(|leaf| leaf.iter().cloned())
+            .collect::<Vec<_>>();
+        assert_eq!(leaves, tree.items());
+    }
+
+    #[test]
+    fn leaf_trees() {
+        let mut tree = Tree::new();
+        assert_eq!(tree.leaf_trees().count(), 0);
+
+        tree.extend(0..50u16);
+        let items = tree
+            .leaf_trees()
+            .map(|leaf| leaf.last().unwrap().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(items, tree.items());
+
+        let mut shared_tree = Tree::new();
+        for leaf in tree.leaf_trees() {
+            shared_tree.push_tree(leaf.clone());
+        }
+        assert_eq!(shared_tree, tree);
+        for (leaf, shared_leaf) in tree.leaf_trees().zip(shared_tree.leaf_trees()) {
+            assert!(Arc::ptr_eq(&leaf.0, &shared_leaf.0));
+        }
+    }
+
+    #[test]
+    fn rchunks_by() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        let mut tree = Tree::new();
+        assert_eq!(tree.rchunks_by(Sum(10)).count(), 0);
+
+        tree.extend(vec![3, 4, 12, 5, 0, 2u16]);
+        assert_eq!(
+            tree.rchunks_by(Sum(10))
+                .map(|chunk| chunk.items())
+                .collect::<Vec<_>>(),
+            vec![vec![5, 0, 2], vec![12], vec![3, 4]]
+        );
+
+        let mut rng = StdRng::from_seed(&[8]);
+        let mut tree = Tree::new();
+        tree.extend((0..500).map(|_| rng.gen_range(0, 10u16)));
+        for budget in 1..25 {
+            let mut chunks = tree.rchunks_by(Sum(budget)).collect::<Vec<_>>();
+            chunks.reverse();
+            let mut concatenation = Tree::new();
+            for chunk in chunks {
+                let chunk_len = chunk.len::<Sum>().0;
+                assert!(
+                    chunk_len <= budget
+                        || chunk.items().iter().filter(|&&item| item > 0).count() == 1
+                );
+                concatenation.push_tree(chunk);
+            }
+            assert_eq!(concatenation, tree);
+        }
+    }
+
+    #[test]
+    fn longest_run() {
+        let is_even = |summary: &IntegersSummary| summary.sum % 2 == 0;
+        let mut tree = Tree::new();
+        assert_eq!(tree.longest_run::<Count, _>(is_even), Count(0)..Count(0));
+
+        tree.extend(vec![1, 2, 4, 3, 6, 8, 10, 5, 2, 2, 2]);
+        assert_eq!(tree.longest_run::<Count, _>(is_even), Count(4)..Count(7));
+        assert_eq!(tree.longest_run::<Sum, _>(is_even), Sum(10)..Sum(34));
+
+        // Ties go to the first run.
+        let mut tree = Tree::new();
+        tree.extend(vec![2, 4, 1, 6, 8, 1]);
+        assert_eq!(tree.longest_run::<Count, _>(is_even), Count(0)..Count(2));
+        let mut tree = Tree::new();
+        tree.extend(vec![1, 3, 5]);
+        assert_eq!(tree.longest_run::<Count, _>(is_even), Count(0)..Count(0));
+    }
+
+    #[test]
+    fn try_get() {
+        let mut tree = Tree::new();
+        tree.extend(1..=10u16);
+        assert_eq!(tree.try_get(&Count(0)), Ok(&1));
+        assert_eq!(tree.try_get(&Count(9)), Ok(&10));
+        assert_eq!(tree.try_get(&Sum(3)), Ok(&3));
+        assert_eq!(tree.try_get(&Sum(54)), Ok(&10));
+        assert_eq!(
+            tree.try_get(&Count(10)),
+            Err(OutOfRange {
+                position: Count(10),
+                len: Count(10),
+            })
+        );
+        assert_eq!(
+            tree.try_get(&Sum(100)),
+            Err(OutOfRange {
+                position: Sum(100),
+                len: Sum(55),
+            })
+        );
+        assert_eq!(
+            Tree::<u16>::new().try_get(&Count(0)),
+            Err(OutOfRange {
+                position: Count(0),
+                len: Count(0),
+            })
+        );
+    }
+
+    #[test]
+    fn leaf_arcs_in_range() {
+        let mut tree = Tree::new();
+        tree.extend(1..=10u16);
+        let leaf_items = |leaves: Vec<Tree<u16>>| {
+            leaves
+                .iter()
+                .flat_map(|leaf| leaf.iter().cloned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            leaf_items(tree.leaf_arcs_in_range(&Count(2)..&Count(5))),
+            vec![3, 4, 5]
+        );
+        assert!(tree.leaf_arcs_in_range(&Count(5)..&Count(5)).is_empty());
+
+        // A sum of 2 falls inside item 2, and a sum of 11 inside item 5.
+        assert_eq!(
+            leaf_items(tree.leaf_arcs_in_range(&Sum(2)..&Sum(11))),
+            vec![2, 3, 4, 5]
+        );
+        assert_eq!(
+            leaf_items(tree.leaf_arcs_in_range(&Sum(3)..&Sum(6))),
+            vec![3]
+        );
+        assert_eq!(
+            leaf_items(tree.leaf_arcs_in_range(&Sum(50)..&Sum(100))),
+            vec![10]
+        );
+
+        let leaves = tree.leaf_arcs_in_range(&Count(0)..&Count(10));
+        for (leaf, tree_leaf) in leaves.iter().zip(tree.leaf_trees()) {
+            assert!(Arc::ptr_eq(&leaf.0, &tree_leaf.0));
+        }
+    }
+
+    #[test]
+    fn position_of() {
+        let mut tree = Tree::new();
+        tree.extend(1..=10u16);
+        let reaches = |target| move |summary: &IntegersSummary| summary.sum >= target;
+        assert_eq!(tree.position_of(reaches(0)), Some(Count(0)));
+        assert_eq!(tree.position_of(reaches(1)), Some(Count(1)));
+        assert_eq!(tree.position_of(reaches(10)), Some(Count(4)));
+        assert_eq!(tree.position_of(reaches(11)), Some(Count(5)));
+        assert_eq!(tree.position_of(reaches(11)), Some(Sum(15)));
+        assert_eq!(tree.position_of(reaches(55)), Some(Count(10)));
+        assert_eq!(tree.position_of::<Count, _>(reaches(56)), None);
+        assert_eq!(Tree::<u16>::new().position_of::<Count, _>(reaches(1)), None);
+
+        let mut tree = Tree::new();
+        tree.extend((0..1000u16).map(|i| i % 7));
+        let mut running_sum = 0;
+        let expected_position = tree
+            .iter()
+            .position(|&item| {
+                running_sum += item as usize;
+                running_sum >= 2000
+            })
+            .map(|index| Count(index + 1));
+        assert_eq!(tree.position_of(reaches(2000)), expected_position);
+    }
+
+    #[test]
+    fn summaries() {
+        let mut tree = Tree::new();
+        assert_eq!(tree.summaries().count(), 0);
+
+        tree.extend((0..100u16).map(|item| item * 7 % 31));
+        let mut summary = IntegersSummary::default();
+        for (item_summary, item) in tree.summaries().zip(tree.iter()) {
+            assert_eq!(item_summary, &item.summarize());
+            summary += item_summary;
+        }
+        assert_eq!(&summary, tree.summary());
+        assert_eq!(tree.summaries().count(), tree.iter().count());
+    }
+
+    #[test]
+    fn for_each_height1_subtree() {
+        Tree::<u16>::new().for_each_height1_subtree(|_| panic!("Visited an empty tree"));
+
+        let mut tree = Tree::new();
+        tree.extend(0..500u16);
+        let mut visited_leaves = Vec::new();
+        let mut summary = IntegersSummary::default();
+        tree.for_each_height1_subtree(|subtree| {
+            assert_eq!(subtree.height(), 1);
+            assert!(subtree.leaf_trees().count() <= MAX_CHILDREN);
+            visited_leaves.extend(subtree.leaf_trees().map(|leaf| leaf.0.as_ref() as *const _));
+            summary += subtree.summary();
+        });
+        let leaves = tree
+            .leaf_trees()
+            .map(|leaf| leaf.0.as_ref() as *const _)
+            .collect::<Vec<_>>();
+        assert_eq!(visited_leaves, leaves);
+        assert_eq!(&summary, tree.summary());
+    }
+
+    #[test]
+    fn runs() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        assert_eq!(Tree::<u16>::new().runs().count(), 0);
+        assert_eq!(
+            Tree::from_item(7u16).runs().collect::<Vec<_>>(),
+            vec![(&7, 1)]
+        );
+
+        let mut rng = StdRng::from_seed(&[3]);
+        let items = (0..500).map(|_| rng.gen_range(0, 3)).collect::<Vec<u16>>();
+        let mut expected_runs = Vec::new();
+        let mut run_start = 0;
+        for index in 1..items.len() + 1 {
+            if index == items.len() || items[index] != items[run_start] {
+                expected_runs.push((&items[run_start], index - run_start));
+                run_start = index;
+            }
+        }
+
+        let mut tree = Tree::new();
+        tree.extend(items.iter().cloned());
+        assert_eq!(tree.runs().collect::<Vec<_>>(), expected_runs);
+    }
+
+    #[test]
+    fn seek_empty_tree() {
+        let tree = Tree::<u16>::new();
+        let mut cursor = tree.cursor();
+        for &bias in &[SeekBias::Left, SeekBias::Right] {
+            for &pos in &[0, 1, 10] {
+                cursor.seek(&Sum(pos), bias);
+                assert_eq!(cursor.item(), None);
+                assert_eq!(cursor.prev_item(), None);
+                assert_eq!(cursor.start::<Sum>(), Sum(0));
+                assert!(cursor.at_end());
+
+                cursor.reset();
+                assert!(cursor.slice(&Sum(pos), bias).is_empty());
+                assert_eq!(cursor.item(), None);
+                assert_eq!(cursor.start::<Sum>(), Sum(0));
+                assert!(cursor.at_end());
+            }
+        }
+
+        // Seeking past the end of a non-empty tree also lands at the end.
+        let mut tree = Tree::new();
+        tree.extend(1..=3u16);
+        let mut cursor = tree.cursor();
+        for &bias in &[SeekBias::Left, SeekBias::Right] {
+            cursor.seek(&Sum(10), bias);
+            assert_eq!(cursor.item(), None);
+            assert_eq!(cursor.prev_item(), Some(&3));
+            assert_eq!(cursor.start::<Sum>(), Sum(6));
+            assert!(cursor.at_end());
+        }
+    }
+
+    #[test]
+    fn cursor_at_start_and_end() {
+        let tree = Tree::<u16>::new();
+        let cursor = tree.cursor();
+        assert!(cursor.at_start());
+        assert!(cursor.at_end());
+
+        let mut tree = Tree::new();
+        tree.extend(1..=20u16);
+        let mut cursor = tree.cursor();
+        assert!(cursor.at_start());
+        assert!(!cursor.at_end());
+
+        cursor.seek(&Count(0), SeekBias::Right);
+        let mut items = Vec::new();
+        while !cursor.at_end() {
+            assert_eq!(cursor.at_start(), items.is_empty());
+            items.push(*cursor.item().unwrap());
+            cursor.next();
+        }
+        assert_eq!(items, (1..=20).collect::<Vec<_>>());
+        assert!(!cursor.at_start());
+        assert_eq!(cursor.item(), None);
+
+        items.clear();
+        while !cursor.at_start() {
+            cursor.prev();
+            assert!(!cursor.at_end());
+            items.push(*cursor.item().unwrap());
+        }
+        assert_eq!(items, (1..=20).rev().collect::<Vec<_>>());
+        assert_eq!(cursor.start::<Count>(), Count(0));
+
+        cursor.seek(&Count(20), SeekBias::Right);
+        assert!(cursor.at_end());
+        cursor.seek(&Count(19), SeekBias::Right);
+        assert!(!cursor.at_end());
+        assert!(!cursor.at_start());
+    }
+
+    #[test]
+    fn cursor_path() {
+        let tree = Tree::<u16>::new();
+        let mut cursor = tree.cursor();
+        cursor.seek(&Count(0), SeekBias::Right);
+        assert_eq!(cursor.path(), Vec::<usize>::new());
+
+        // Pushing 8 items one at a time splits the root's children into [1, 2, 3], [4, 5, 6]
+        // and [7, 8].
+        let mut tree = Tree::new();
+        tree.extend(1..=8u16);
+        let mut cursor = tree.cursor();
+        cursor.seek(&Count(0), SeekBias::Right);
+        assert_eq!(cursor.path(), vec![0, 0]);
+        cursor.seek(&Count(4), SeekBias::Right);
+        assert_eq!(cursor.item(), Some(&5));
+        assert_eq!(cursor.path(), vec![1, 1]);
+        cursor.seek(&Count(7), SeekBias::Right);
+        assert_eq!(cursor.item(), Some(&8));
+        assert_eq!(cursor.path(), vec![2, 1]);
+        cursor.next();
+        assert_eq!(cursor.path(), Vec::<usize>::new());
+
+        let mut tree = Tree::new();
+        tree.extend(0..100u16);
+        let mut cursor = tree.cursor();
+        cursor.seek(&Count(0), SeekBias::Right);
+        while let Some(item) = cursor.item() {
+            let mut subtree = &tree;
+            for index in cursor.path() {
+                subtree = &subtree.children()[index];
+            }
+            assert_eq!(subtree.value(), item);
+            cursor.next();
+        }
+    }
+
+    #[test]
+    fn range_summary() {
+        let mut tree = Tree::new();
+        tree.extend(0..10u16);
+        let mut expected_tree = Tree::new();
+        expected_tree.extend(2..5u16);
+        assert_eq!(
+            tree.range_summary(&Count(2)..&Count(5)),
+            expected_tree.total_summary()
+        );
+        assert_eq!(
+            tree.range_summary(&Count(0)..&Count(10)),
+            tree.total_summary()
+        );
+        assert_eq!(
+            tree.range_summary(&Count(5)..&Count(5)),
+            IntegersSummary::default()
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_range_summary() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        let mut rng = StdRng::from_seed(&[11]);
+        let mut tree = Tree::new();
+        tree.extend(rng.gen_iter::<u16>().map(|item| item % 10).take(2000));
+        let len = tree.len::<Sum>().0;
+        for _ in 0..200 {
+            let start = rng.gen_range(0, len + 1);
+            let end = rng.gen_range(start, len + 1);
+            assert_eq!(
+                tree.par_range_summary(&Sum(start)..&Sum(end)),
+                tree.range_summary(&Sum(start)..&Sum(end))
+            );
+
+            let start = rng.gen_range(0, 2001);
+            let end = rng.gen_range(start, 2001);
+            assert_eq!(
+                tree.par_range_summary(&Count(start)..&Count(end)),
+                tree.range_summary(&Count(start)..&Count(end))
+            );
+        }
+        assert_eq!(
+            Tree::<u16>::new().par_range_summary(&Count(0)..&Count(0)),
+            IntegersSummary::default()
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_extend() {
+        use rayon::prelude::*;
+
+        let mut tree = Tree::new();
+        tree.extend(0..100u16);
+        let mut par_tree = tree.clone();
+        tree.extend((0..50000u32).map(|item| (item % 1000) as u16));
+        par_tree.par_extend(
+            (0..50000u32)
+                .into_par_iter()
+                .map(|item| (item % 1000) as u16),
+        );
+        assert_eq!(par_tree, tree);
+        assert_eq!(par_tree.summary(), tree.summary());
+        assert_fanout(&par_tree, MIN_CHILDREN, MAX_CHILDREN);
+
+        let mut empty_tree = Tree::<u16>::new();
+        empty_tree.par_extend(Vec::new());
+        assert!(empty_tree.is_empty());
+    }
+
+    #[test]
+    fn items_in_range() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        let mut tree = Tree::new();
+        assert_eq!(tree.items_in_range(&Count(0)..&Count(10)).count(), 0);
+
+        let mut rng = StdRng::from_seed(&[12]);
+        tree.extend((0..300).map(|_| rng.gen_range(0, 5u16)));
+        let count = tree.len::<Count>().0;
+        let sum = tree.len::<Sum>().0;
+        for _ in 0..100 {
+            let end = rng.gen_range(0, count + 2);
+            let start = rng.gen_range(0, end + 1);
+            assert_eq!(
+                tree.items_in_range(&Count(start)..&Count(end))
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                tree.get_range_as_vec(&Count(start)..&Count(end))
+            );
+
+            let end = rng.gen_range(0, sum + 2);
+            let start = rng.gen_range(0, end + 1);
+            assert_eq!(
+                tree.items_in_range(&Sum(start)..&Sum(end))
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                tree.get_range_as_vec(&Sum(start)..&Sum(end))
+            );
+        }
+    }
+
+    #[test]
+    fn span() {
+        let mut tree = Tree::new();
+        tree.extend(1..=10u16);
+        assert_eq!(tree.span(&Count(2)..&Count(5)), Count(3));
+        assert_eq!(tree.span(&Count(0)..&Count(10)), Count(10));
+        assert_eq!(tree.span(&Count(4)..&Count(4)), Count(0));
+        assert_eq!(tree.span(&Sum(3)..&Sum(15)), Sum(12));
+        assert_eq!(tree.span(&Sum(0)..&Sum(55)), Sum(55));
+
+        // A range inside items spans the items ending within it.
+        assert_eq!(tree.span(&Sum(2)..&Sum(7)), Sum(5));
+    }
+
+    #[test]
+    fn get_range_as_vec() {
+        let mut tree = Tree::new();
+        tree.extend(0..10u16);
+        assert_eq!(tree.get_range_as_vec(&Count(2)..&Count(5)), [2, 3, 4]);
+        assert_eq!(tree.get_range_as_vec(&Count(0)..&Count(10)), tree.items());
+        assert_eq!(
+            tree.get_range_as_vec(&Count(5)..&Count(5)),
+            Vec::<u16>::new()
+        );
+        assert_eq!(tree.get_range_as_vec(&Count(8)..&Count(20)), [8, 9]);
+        assert_eq!(tree.get_range_as_vec(&Sum(3)..&Sum(10)), [3, 4]);
+        assert_eq!(tree.get_range_as_vec(&Sum(4)..&Sum(7)), [3, 4]);
+        assert_eq!(tree.get_range_as_vec(&Sum(4)..&Sum(6)), [3]);
+    }
+
+    #[test]
+    fn max_by_key_summary() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        assert_eq!(
+            Tree::<u16>::new().max_by_key_summary(|summary| summary.max),
+            None
+        );
+
+        for seed in 0..20 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let mut tree = Tree::new();
+            let len = rng.gen_range(1, 200);
+            tree.extend(rng.gen_iter::<u16>().take(len));
+            let max = tree.max_by_key_summary(MaxSummary::max_key);
+            assert_eq!(max, tree.iter().max());
+        }
+
+        // Ties resolve to the first item with the greatest key.
+        let mut tree = Tree::new();
+        tree.extend(vec![1, 5, 2, 5, 3]);
+        let max = tree.max_by_key_summary(|summary| summary.max).unwrap();
+        assert!(max as *const u16 == tree.iter().nth(1).unwrap() as *const u16);
+    }
+
+    #[test]
+    fn try_from_sorted_iter() {
+        let tree = Tree::try_from_sorted_iter::<Sum, _>(vec![1, 1, 2, 5, 8u16]).unwrap();
+        assert_eq!(tree.items(), [1, 1, 2, 5, 8]);
+        assert!(tree.is_sorted_by::<Sum>());
+        assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+
+        assert!(Tree::<u16>::try_from_sorted_iter::<Sum, _>(None)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            Tree::try_from_sorted_iter::<Sum, _>(vec![1, 3, 2, 5, 4u16]).map(|tree| tree.items()),
+            Err(UnsortedError { index: 2 })
+        );
+    }
+
+    #[test]
+    fn is_sorted_by() {
+        assert!(Tree::<u16>::new().is_sorted_by::<Sum>());
+
+        let mut tree = Tree::new();
+        tree.extend(vec![1, 2, 2, 5, 8, 13]);
+        assert!(tree.is_sorted_by::<Sum>());
+        tree.push(12);
+        assert!(!tree.is_sorted_by::<Sum>());
+        assert!(tree.is_sorted_by::<Count>());
+    }
+
+    #[test]
+    fn contains_and_position() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        for seed in 0..20 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let count = rng.gen_range(0, 50);
+            let reference_items = (0..count)
+                .map(|_| rng.gen_range(0, 20))
+                .collect::<Vec<u16>>();
+            let mut tree = Tree::new();
+            tree.extend(reference_items.iter());
+
+            for item in 0..20 {
+                assert_eq!(tree.contains(&item), reference_items.contains(&item));
+                assert_eq!(
+                    tree.position(&item),
+                    reference_items.iter().position(|other| *other == item)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn split_off() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        for seed in 0..20 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let count = rng.gen_range(0, 50);
+            let mut tree = Tree::new();
+            tree.extend(rng.gen_iter::<u16>().take(count));
+            let reference_items = tree.items();
+
+            let mut prefix = tree.clone();
+            let split_at = rng.gen_range(0, count + 1);
+            let suffix = prefix.split_off(&Count(split_at));
+            assert_eq!(prefix.items(), reference_items[..split_at].to_vec());
+            assert_eq!(suffix.items(), reference_items[split_at..].to_vec());
+
+            prefix.push_tree(suffix);
+            assert_eq!(prefix.items(), reference_items);
+
+            let mut prefix = tree.clone();
+            assert_eq!(prefix.split_off(&Count(0)).items(), reference_items);
+            assert!(prefix.is_empty());
+
+            let mut prefix = tree.clone();
+            assert!(prefix.split_off(&Count(count)).is_empty());
+            assert_eq!(prefix.items(), reference_items);
+        }
+    }
+
+    #[test]
+    fn append() {
+        let mut tree1 = Tree::new();
+        tree1.extend(1..20);
+        let mut tree2 = Tree::new();
+        tree2.extend(1..50);
+
+        tree1.append(&mut tree2);
+        assert!(tree2.is_empty());
+        assert_eq!(tree1.items(), (1..20).chain(1..50).collect::<Vec<u16>>());
+
+        tree1.append(&mut tree2);
+        assert_eq!(tree1.items(), (1..20).chain(1..50).collect::<Vec<u16>>());
+    }
+
+    #[test]
+    fn scan_until() {
+        let mut tree = Tree::new();
+        tree.extend(1..7);
+        assert_eq!(tree.scan_until(|sum: &Sum| sum.0 > 10), Sum(15));
+        assert_eq!(tree.scan_until(|sum: &Sum| sum.0 >= 10), Sum(10));
+        assert_eq!(tree.scan_until(|_: &Sum| true), Sum(0));
+        assert_eq!(tree.scan_until(|sum: &Sum| sum.0 > 100), Sum(21));
+
+        let mut visited = 0;
+        let mut tree = Tree::new();
+        tree.extend(0..1000);
+        assert_eq!(
+            tree.scan_until(|count: &Count| {
+                visited += 1;
+                count.0 > 500
+            }),
+            Count(501)
+        );
+        assert!(visited < 100);
+    }
+
+    #[test]
+    fn combined_summary() {
+        let mut trees = Vec::new();
+        let mut concatenation = Tree::new();
+        for range in vec![0..10, 5..7, 0..0, 100..200] {
+            let mut tree = Tree::new();
+            tree.extend(range);
+            concatenation.push_tree(tree.clone());
+            trees.push(tree);
+        }
+
+        assert_eq!(
+            Tree::combined_summary(&trees),
+            concatenation.total_summary()
+        );
+        assert_eq!(
+            Tree::<u16>::combined_summary(&[]),
+            IntegersSummary::default()
+        );
+    }
+
+    #[cfg(feature = "internals")]
+    #[test]
+    fn root_ptr() {
+        let mut tree = Tree::new();
+        tree.extend(0..10);
+        let clone = tree.clone();
+        assert_eq!(tree.root_ptr(), clone.root_ptr());
+
+        tree.push(10);
+        assert_ne!(tree.root_ptr(), clone.root_ptr());
+    }
+
+    #[test]
+    fn eq_skips_shared_subtrees() {
+        use std::cell::Cell;
+
+        thread_local! {
+            static COMPARISONS: Cell<usize> = Cell::new(0);
+        }
+
+        #[derive(Clone, Eq, Debug)]
+        struct Tracked(u16);
+
+        impl PartialEq for Tracked {
+            fn eq(&self, other: &Self) -> bool {
+                COMPARISONS.with(|comparisons| comparisons.set(comparisons.get() + 1));
+                self.0 == other.0
+            }
+        }
+
+        impl Item for Tracked {
+            type Summary = IntegersSummary;
+
+            fn summarize(&self) -> Self::Summary {
+                self.0.summarize()
+            }
+        }
+
+        let mut tree = Tree::new();
+        tree.extend((0..10000).map(Tracked));
+        for &index in &[0, 3333, 9999] {
+            let mut edited_tree = tree.clone();
+            edited_tree.splice(
+                &Count(index)..&Count(index + 1),
+                Some(Tracked(index as u16)),
+            );
+            COMPARISONS.with(|comparisons| comparisons.set(0));
+            assert_eq!(edited_tree, tree);
+            assert!(COMPARISONS.with(|comparisons| comparisons.get()) <= 20);
+
+            edited_tree.splice(&Count(index)..&Count(index + 1), Some(Tracked(1)));
+            assert_ne!(edited_tree, tree);
+        }
+
+        // Trees with the same items but different shapes are still equal.
+        let mut balanced_tree = Tree::new();
+        balanced_tree.extend_balanced((0..10000).map(Tracked));
+        assert_eq!(balanced_tree, tree);
+    }
+
+    #[test]
+    fn edits_only_summarize_new_items() {
+        use std::cell::Cell;
+
+        thread_local! {
+            static SUMMARIZE_CALLS: Cell<usize> = Cell::new(0);
+        }
+
+        #[derive(Clone, Eq, PartialEq, Debug)]
+        struct Tracked(u16);
+
+        impl Item for Tracked {
+            type Summary = IntegersSummary;
+
+            fn summarize(&self) -> Self::Summary {
+                SUMMARIZE_CALLS.with(|calls| calls.set(calls.get() + 1));
+                self.0.summarize()
+            }
+        }
+
+        fn summarize_calls() -> usize {
+            SUMMARIZE_CALLS.with(|calls| calls.replace(0))
+        }
+
+        let mut tree = Tree::new();
+        tree.extend((0..1000).map(Tracked));
+        assert_eq!(summarize_calls(), 1000);
+
+        for &index in &[0, 500, 999] {
+            tree.splice(&Count(index)..&Count(index + 1), (0..3).map(Tracked));
+            assert_eq!(summarize_calls(), 3);
+        }
+
+        let mut other_tree = Tree::new();
+        other_tree.extend((0..100).map(Tracked));
+        summarize_calls();
+        tree.append(&mut other_tree);
+        let suffix = tree.split_off(&Count(300));
+        tree.push_tree(suffix);
+        assert_eq!(summarize_calls(), 0);
+    }
+
+    #[test]
+    fn update_last() {
+        let mut tree = Tree::new();
+        assert!(!tree.update_last(|_: &mut u16| panic!("Called on an empty tree")));
+
+        tree.extend(1..=100u16);
+        let snapshot = tree.clone();
+        assert!(tree.update_last(|item| *item = 1000));
+        assert_eq!(tree.last(), Some(&1000));
+        assert_eq!(tree.summary().sum, 5050 - 100 + 1000);
+        assert_eq!(tree.summary().max, 1000);
+        assert!(tree.summary_is_consistent());
+        assert_eq!(tree.items(), (1..100).chain(Some(1000)).collect::<Vec<_>>());
+        assert_eq!(snapshot.items(), (1..=100).collect::<Vec<_>>());
+
+        // Leaves that weren't shared are modified without being copied.
+        let last_leaf_ptr =
+            |tree: &Tree<u16>| tree.leaf_trees().last().unwrap().0.as_ref() as *const Node<u16>;
+        let prev_last_leaf = last_leaf_ptr(&tree);
+        tree.update_last(|item| *item += 1);
+        assert_eq!(last_leaf_ptr(&tree), prev_last_leaf);
+        assert_eq!(tree.last(), Some(&1001));
+        assert_eq!(tree.last_summary().map(|summary| summary.sum), Some(1001));
+    }
+
+    #[test]
+    fn eq_slice() {
+        let mut tree = Tree::new();
+        tree.extend(1..=3u16);
+        assert_eq!(tree, [1, 2, 3][..]);
+        assert_eq!(tree, vec![1, 2, 3]);
+        assert_ne!(tree, [1, 2][..]);
+        assert_ne!(tree, vec![1, 2, 3, 4]);
+        assert_ne!(tree, vec![1, 5, 3]);
+        assert_eq!(Tree::<u16>::new(), Vec::new());
+        assert_ne!(Tree::new(), [1][..]);
+    }
+
+    #[test]
+    fn eq_by() {
+        let low_byte_eq = |a: &u16, b: &u16| a & 0xff == b & 0xff;
+
+        let mut tree1 = Tree::new();
+        tree1.extend(0..100);
+        let mut tree2 = Tree::new();
+        tree2.extend((0..100).map(|i| i + 0x100));
+        assert!(tree1.eq_by(&tree2, low_byte_eq));
+        assert!(!tree1.eq_by(&tree2, |a, b| a == b));
+
+        tree2.push(100);
+        assert!(!tree1.eq_by(&tree2, low_byte_eq));
+        assert!(!tree2.eq_by(&tree1, low_byte_eq));
+        assert!(Tree::<u16>::new().eq_by(&Tree::new(), low_byte_eq));
+        assert!(!Tree::new().eq_by(&tree1, low_byte_eq));
+
+        let mut tree3 = tree1.clone();
+        tree3.splice(&Count(50)..&Count(51), Some(1000));
+        let mut comparisons = 0;
+        assert!(!tree1.eq_by(&tree3, |a, b| {
+            comparisons += 1;
+            a == b
+        }));
+        assert!(comparisons < 10);
+    }
+
+    #[test]
+    fn common_prefix_and_suffix_len() {
+        let mut tree1 = Tree::new();
+        tree1.extend(0..1000);
+        assert_eq!(
+            tree1.common_prefix_len::<Count>(&tree1.clone()),
+            Count(1000)
+        );
+        assert_eq!(
+            tree1.common_suffix_len::<Count>(&tree1.clone()),
+            Count(1000)
+        );
+        assert_eq!(tree1.common_prefix_len::<Count>(&Tree::new()), Count(0));
+        assert_eq!(Tree::new().common_suffix_len::<Count>(&tree1), Count(0));
+
+        let mut tree2 = tree1.clone();
+        tree2.splice(&Count(400)..&Count(410), vec![2000, 2001, 2002]);
+        assert_eq!(tree1.common_prefix_len::<Count>(&tree2), Count(400));
+        assert_eq!(tree1.common_suffix_len::<Count>(&tree2), Count(590));
+        assert_eq!(tree2.common_suffix_len::<Count>(&tree1), Count(590));
+        assert_eq!(tree1.common_prefix_len::<Sum>(&tree2), Sum((0..400).sum()));
+
+        // Trees that share no nodes are compared item by item.
+        let mut tree3 = Tree::new();
+        tree3.extend((0..1000).map(|i| if i == 999 { 0 } else { i }));
+        assert_eq!(tree1.common_prefix_len::<Count>(&tree3), Count(999));
+        assert_eq!(tree1.common_suffix_len::<Count>(&tree3), Count(0));
+    }
+
+    #[test]
+    fn shared_regions() {
+        let mut tree1 = Tree::new();
+        tree1.extend(0..1000);
+        assert_eq!(
+            tree1.shared_regions::<Count>(&tree1.clone()),
+            vec![Count(0)..Count(1000)]
+        );
+        let mut unrelated_tree = Tree::new();
+        unrelated_tree.extend(0..1000);
+        assert_eq!(tree1.shared_regions::<Count>(&unrelated_tree), vec![]);
+        assert_eq!(Tree::new().shared_regions::<Count>(&tree1), vec![]);
+
+        let mut tree2 = tree1.clone();
+        tree2.splice(&Count(500)..&Count(501), Some(5000));
+        for &(ref tree, ref other_tree) in &[(&tree1, &tree2), (&tree2, &tree1)] {
+            let regions = tree.shared_regions::<Count>(other_tree);
+            assert_eq!(regions.len(), 2);
+            assert_eq!(regions[0].start, Count(0));
+            assert!(regions[0].end <= Count(500) && regions[0].end >= Count(450));
+            assert!(regions[1].start >= Count(501) && regions[1].start <= Count(550));
+            assert_eq!(regions[1].end, Count(1000));
+        }
+
+        // Regions refer to the receiver's positions, even when the other tree's items moved.
+        let mut tree3 = tree1.clone();
+        tree3.splice(&Count(0)..&Count(0), vec![1; 10]);
+        let regions = tree1.shared_regions::<Count>(&tree3);
+        assert_eq!(regions.last().unwrap().end, Count(1000));
+        let shared_count = regions
+            .iter()
+            .map(|region| region.end.0 - region.start.0)
+            .sum::<usize>();
+        assert!(shared_count >= 900);
+        for region in &regions {
+            let moved_region = Count(region.start.0 + 10)..Count(region.end.0 + 10);
+            assert_eq!(
+                tree1.get_range_as_vec(&region.start..&region.end),
+                tree3.get_range_as_vec(&moved_region.start..&moved_region.end)
+            );
+        }
+    }
+
+    #[test]
+    fn diff() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        let mut tree1 = Tree::new();
+        tree1.extend(1..=10);
+        let mut tree2 = tree1.clone();
+        tree2.splice(&Count(3)..&Count(5), Some(20));
+        assert_eq!(
+            tree1.diff::<Sum>(&tree2),
+            vec![
+                Edit::Insert {
+                    position: Sum(6),
+                    items: vec![20],
+                },
+                Edit::Remove {
+                    range: Sum(6)..Sum(15),
+                },
+            ]
+        );
+        assert_eq!(tree1.diff::<Sum>(&tree1.clone()), vec![]);
+        assert_eq!(
+            Tree::new().diff::<Count>(&tree1),
+            vec![Edit::Insert {
+                position: Count(0),
+                items: (1..=10).collect(),
+            }]
+        );
+        assert_eq!(
+            tree1.diff::<Count>(&Tree::new()),
+            vec![Edit::Remove {
+                range: Count(0)..Count(10),
+            }]
+        );
+
+        for seed in 0..20 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let mut tree1 = Tree::new();
+            let items_count = rng.gen_range(0, 500);
+            tree1.extend((0..items_count).map(|_| rng.gen_range(0, 4)));
+            let mut tree2 = tree1.clone();
+            for _ in 0..rng.gen_range(0, 3) {
+                let end = rng.gen_range(0, tree2.len::<Count>().0 + 1);
+                let start = rng.gen_range(0, end + 1);
+                let new_items = (0..rng.gen_range(0, 5)).map(|_| rng.gen_range(0, 4));
+                tree2.splice(&Count(start)..&Count(end), new_items.collect::<Vec<_>>());
+            }
+
+            let edits = tree1.diff::<Count>(&tree2);
+            let mut patched_tree = tree1.clone();
+            patched_tree.apply_edits(&edits);
+            assert_eq!(patched_tree.items(), tree2.items());
+        }
+    }
+
+    #[test]
+    fn splice() {
+        let mut tree = Tree::new();
+        tree.extend(0..10);
+        tree.splice(&Count(2)..&Count(8), 20..23);
+        assert_eq!(tree.items(), vec![0, 1, 20, 21, 22, 8, 9]);
+    }
+
+    #[test]
+    fn splice_reporting() {
+        let mut tree = Tree::new();
+        tree.extend(1..=10);
+        let edit = tree.splice_reporting::<_, Sum, _>(&Count(2)..&Count(5), vec![20, 21]);
+        assert_eq!(tree.items(), vec![1, 2, 20, 21, 6, 7, 8, 9, 10]);
+        assert_eq!(
+            edit,
+            EditSummary {
+                start: Sum(3),
+                old_len: Sum(12),
+                new_len: Sum(41),
+            }
+        );
+        assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+
+        let edit = tree.splice_reporting::<_, Count, _>(&Sum(84)..&Sum(84), Some(30));
+        assert_eq!(tree.items(), vec![1, 2, 20, 21, 6, 7, 8, 9, 10, 30]);
+        assert_eq!(
+            edit,
+            EditSummary {
+                start: Count(9),
+                old_len: Count(0),
+                new_len: Count(1),
+            }
+        );
+
+        let mut tree = Tree::new();
+        tree.extend(vec![2, 2, 2]);
+        let mut spliced = tree.clone();
+        spliced.splice(&Sum(3)..&Sum(3), Some(9));
+        let edit = tree.splice_reporting::<_, Count, _>(&Sum(3)..&Sum(3), Some(9));
+        assert_eq!(tree.items(), vec![2, 2, 9, 2]);
+        assert_eq!(tree, spliced);
+        assert_eq!(
+            edit,
+            EditSummary {
+                start: Count(2),
+                old_len: Count(0),
+                new_len: Count(1),
+            }
+        );
+
+        // The range starts inside 10 and ends inside 20, so 3, 1 and 20 are replaced.
+        let mut tree = Tree::new();
+        tree.extend(vec![10, 3, 1, 20]);
+        let mut spliced = tree.clone();
+        spliced.splice(&Sum(5)..&Sum(15), Some(7));
+        let edit = tree.splice_reporting::<_, Sum, _>(&Sum(5)..&Sum(15), Some(7));
+        assert_eq!(tree.items(), vec![10, 7]);
+        assert_eq!(tree, spliced);
+        assert_eq!(
+            edit,
+            EditSummary {
+                start: Sum(10),
+                old_len: Sum(24),
+                new_len: Sum(7),
+            }
+        );
+    }
+
+    #[test]
+    fn try_splice() {
+        let mut tree = Tree::new();
+        tree.extend(0..10);
+        assert_eq!(
+            tree.try_splice(&Count(8)..&Count(2), 20..23),
+            Err(SpliceError::RangeReversed)
+        );
+        assert_eq!(
+            tree.try_splice(&Count(8)..&Count(11), 20..23),
+            Err(SpliceError::RangeOutOfBounds)
+        );
+        assert_eq!(
+            tree.try_splice(&Count(12)..&Count(11), 20..23),
+            Err(SpliceError::RangeReversed)
+        );
+        assert_eq!(tree.items(), (0..10).collect::<Vec<_>>());
+
+        assert_eq!(tree.try_splice(&Count(10)..&Count(10), 20..23), Ok(()));
+        assert_eq!(tree.try_splice(&Count(0)..&Count(2), None), Ok(()));
+        assert_eq!(tree.items(), vec![2, 3, 4, 5, 6, 7, 8, 9, 20, 21, 22]);
+    }
+
+    #[test]
+    fn split_at_item_boundary() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        // Items stand for chunks of a rope, with the item's value as the chunk's length.
+        let mut tree = Tree::new();
+        tree.extend(vec![3, 5, 2u16]);
+        for &(pos, bias, ref prefix, ref suffix) in &[
+            (0, SeekBias::Left, vec![], vec![3, 5, 2]),
+            (3, SeekBias::Left, vec![3], vec![5, 2]),
+            (3, SeekBias::Right, vec![3], vec![5, 2]),
+            (4, SeekBias::Left, vec![3], vec![5, 2]),
+            (4, SeekBias::Right, vec![3, 5], vec![2]),
+            (9, SeekBias::Right, vec![3, 5, 2], vec![]),
+            (10, SeekBias::Left, vec![3, 5, 2], vec![]),
+        ] {
+            let (left, right) = tree.split_at_item_boundary(&Sum(pos), bias);
+            assert_eq!(&left.items(), prefix);
+            assert_eq!(&right.items(), suffix);
+        }
+
+        let mut rng = StdRng::from_seed(&[5]);
+        let mut tree = Tree::new();
+        tree.extend((0..200).map(|_| rng.gen_range(1, 10u16)));
+        let len = tree.len::<Sum>().0;
+        for _ in 0..50 {
+            let pos = rng.gen_range(0, len + 1);
+            for &bias in &[SeekBias::Left, SeekBias::Right] {
+                let (mut left, mut right) = tree.split_at_item_boundary(&Sum(pos), bias);
+                let boundary = left.len::<Sum>().0;
+                match bias {
+                    SeekBias::Left => assert!(boundary <= pos && pos - boundary < 9),
+                    SeekBias::Right => assert!(boundary >= pos && boundary - pos < 9),
+                }
+                left.append(&mut right);
+                assert_eq!(left, tree);
+            }
+        }
+    }
+
+    #[test]
+    fn take_prefix_while() {
+        let mut tree = Tree::new();
+        tree.extend(1..=10u16);
+        let prefix = tree.take_prefix_while(|summary| summary.sum < 20);
+        assert_eq!(prefix.items(), [1, 2, 3, 4, 5]);
+        assert_eq!(tree.items(), [6, 7, 8, 9, 10]);
+
+        let prefix = tree.take_prefix_while(|summary| summary.sum < 6);
+        assert!(prefix.is_empty());
+        assert_eq!(tree.items(), [6, 7, 8, 9, 10]);
+
+        let prefix = tree.take_prefix_while(|_| true);
+        assert_eq!(prefix.items(), [6, 7, 8, 9, 10]);
+        assert!(tree.is_empty());
+
+        let mut tree = Tree::new();
+        tree.extend((0..1000u16).map(|item| item % 10));
+        let prefix = tree.take_prefix_while(|summary| summary.sum <= 2000);
+        assert_eq!(prefix.len::<Sum>(), Sum(1995));
+        assert_eq!(prefix.len::<Count>(), Count(446));
+        assert_eq!(tree.len::<Count>(), Count(554));
+        assert_fanout(&prefix, MIN_CHILDREN, MAX_CHILDREN);
+        assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+    }
+
+    #[test]
+    fn take_range() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        for seed in 0..20 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let mut tree = Tree::<u16>::new();
+            let count = rng.gen_range(0, 300);
+            tree.extend(rng.gen_iter::<u16>().take(count));
+            let mut reference_items = tree.items();
+            while !tree.is_empty() {
+                let end = rng.gen_range(0, tree.len::<Count>().0 + 1);
+                let start = rng.gen_range(0, end + 1);
+                let taken = tree.take_range(&Count(start)..&Count(end));
+                assert_eq!(
+                    taken.items(),
+                    reference_items.drain(start..end).collect::<Vec<_>>()
+                );
+                assert_eq!(tree.items(), reference_items);
+                assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+                assert_fanout(&taken, MIN_CHILDREN, MAX_CHILDREN);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid range: RangeOutOfBounds")]
+    fn take_range_out_of_bounds() {
+        let mut tree = Tree::new();
+        tree.extend(0..10u16);
+        tree.take_range(&Count(2)..&Count(11));
+    }
+
+    #[test]
+    fn retain_range() {
+        let mut tree = Tree::new();
+        tree.extend(0..10u16);
+        tree.retain_range(&Count(3)..&Count(6));
+        assert_eq!(tree.items(), vec![3, 4, 5]);
+        assert_eq!(tree.len::<Sum>(), Sum(12));
+
+        tree.retain_range(&Sum(3)..&Sum(3));
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn splice_many() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        for seed in 0..20 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let mut tree = Tree::new();
+            let items_count = rng.gen_range(0, 300);
+            tree.extend(rng.gen_iter::<u16>().take(items_count));
+
+            let mut boundaries = (0..rng.gen_range(0, 10) * 2)
+                .map(|_| rng.gen_range(0, items_count + 1))
+                .collect::<Vec<_>>();
+            boundaries.sort();
+            let mut ops = Vec::new();
+            for range in boundaries.chunks(2) {
+                let mut replacement = Tree::new();
+                let replacement_len = rng.gen_range(0, 20);
+                replacement.extend(rng.gen_iter::<u16>().take(replacement_len));
+                ops.push((Count(range[0])..Count(range[1]), replacement));
+            }
+
+            let mut expected_tree = tree.clone();
+            for &(ref range, ref replacement) in ops.iter().rev() {
+                expected_tree.splice_tree(&range.start..&range.end, replacement.clone());
+            }
+            let replacements = ops
+                .iter()
+                .map(|&(_, ref replacement)| replacement.clone())
+                .collect::<Vec<_>>();
+            tree.splice_many(ops);
+            assert_eq!(tree.items(), expected_tree.items());
+            assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+
+            // The replacements' leaves end up in the tree without being copied.
+            for replacement in replacements {
+                for leaf in replacement.leaf_trees() {
+                    assert!(tree
+                        .leaf_trees()
+                        .any(|tree_leaf| Arc::ptr_eq(&tree_leaf.0, &leaf.0)));
+                }
+            }
+        }
+
+        // Ranges starting or ending inside an item select the same items as in `splice_tree`.
+        let mut tree = Tree::new();
+        tree.extend(vec![2, 2, 2]);
+        let mut nine = Tree::new();
+        nine.push(9);
+        let mut seven = Tree::new();
+        seven.push(7);
+        let mut expected_tree = tree.clone();
+        expected_tree.splice_tree(&Sum(3)..&Sum(5), seven.clone());
+        expected_tree.splice_tree(&Sum(1)..&Sum(1), nine.clone());
+        tree.splice_many(vec![(Sum(1)..Sum(1), nine), (Sum(3)..Sum(5), seven)]);
+        assert_eq!(tree.items(), vec![2, 9, 2, 7]);
+        assert_eq!(tree, expected_tree);
+    }
+
+    #[test]
+    fn splice_many_large() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        let mut rng = StdRng::from_seed(&[7]);
+        let mut tree = Tree::new();
+        tree.extend_balanced(rng.gen_iter::<u16>().map(|item| item % 10 + 1).take(20000));
+        let len = tree.len::<Sum>().0;
+
+        let mut boundaries = (0..2000)
+            .map(|_| rng.gen_range(0, len + 1))
+            .collect::<Vec<_>>();
+        boundaries.sort();
+        let mut ops = Vec::new();
+        for range in boundaries.chunks(2) {
+            let mut replacement = Tree::new();
+            let replacement_len = rng.gen_range(0, 4);
+            replacement.extend(rng.gen_iter::<u16>().take(replacement_len));
+            ops.push((Sum(range[0])..Sum(range[1]), replacement));
+        }
+
+        let mut expected_tree = tree.clone();
+        for &(ref range, ref replacement) in ops.iter().rev() {
+            expected_tree.splice_tree(&range.start..&range.end, replacement.clone());
+        }
+        tree.splice_many(ops);
+        assert_eq!(tree, expected_tree);
+        assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+        assert!(tree.summary_is_consistent());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid splice range: RangeOutOfBounds")]
+    fn splice_many_out_of_bounds() {
+        let mut tree = Tree::new();
+        tree.extend(0..10u16);
+        tree.splice_many(vec![
+            (Count(2)..Count(4), Tree::new()),
+            (Count(8)..Count(11), Tree::new()),
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Splice ranges must be ascending and non-overlapping")]
+    fn splice_many_overlapping() {
+        let mut tree = Tree::new();
+        tree.extend(0..10u16);
+        tree.splice_many(vec![
+            (Count(2)..Count(6), Tree::new()),
+            (Count(4)..Count(8), Tree::new()),
+        ]);
+    }
+
+    #[test]
+    fn bulk_remove_sorted() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        for seed in 0..20 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let mut tree = Tree::<u16>::new();
+            let count = rng.gen_range(0, 200);
+            tree.extend(rng.gen_iter::<u16>().take(count));
+
+            let positions = (0..count + 1)
+                .filter(|_| rng.gen_weighted_bool(3))
+                .map(Count)
+                .collect::<Vec<_>>();
+            let mut reference_tree = tree.clone();
+            for position in positions.iter().rev() {
+                if position.0 < count {
+                    reference_tree.splice(position..&Count(position.0 + 1), None);
+                }
+            }
+
+            tree.bulk_remove_sorted(&positions);
+            assert_eq!(tree.items(), reference_tree.items());
+            assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+        }
+
+        let mut tree = Tree::new();
+        tree.extend(1..=5u16);
+        tree.bulk_remove_sorted(&[Sum(2), Sum(6), Sum(14)]);
+        assert_eq!(tree.items(), [1, 3]);
+    }
+
+    #[test]
+    fn apply_edits() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        for seed in 0..50 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let mut tree = Tree::<u16>::new();
+            let count = rng.gen_range(0, 100);
+            tree.extend(rng.gen_iter::<u16>().take(count));
+
+            let mut edits = Vec::new();
+            let mut position = 0;
+            loop {
+                position = rng.gen_range(position, count + 1);
+                if rng.gen_weighted_bool(2) {
+                    let items_count = rng.gen_range(0, 5);
+                    let items = rng.gen_iter().take(items_count).collect();
+                    edits.push(Edit::Insert {
+                        position: Count(position),
+                        items,
+                    });
+                } else {
+                    let end = rng.gen_range(position, count + 1);
+                    edits.push(Edit::Remove {
+                        range: Count(position)..Count(end),
+                    });
+                    position = end;
+                }
+                if position == count || rng.gen_weighted_bool(5) {
+                    break;
+                }
+            }
+
+            let mut reference_items = tree.items();
+            for edit in edits.iter().rev() {
+                match edit {
+                    &Edit::Insert {
+                        ref position,
+                        ref items,
+                    } => {
+                        reference_items.splice(position.0..position.0, items.iter().cloned());
+                    }
+                    &Edit::Remove { ref range } => {
+                        reference_items.drain(range.start.0..range.end.0);
+                    }
+                }
+            }
+
+            tree.apply_edits(&edits);
+            assert_eq!(tree.items(), reference_items);
+            assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Inconsistent edit position: Count(3)")]
+    fn apply_edits_out_of_order() {
+        let mut tree = Tree::new();
+        tree.extend(0..10u16);
+        tree.apply_edits(&[
+            Edit::Remove {
+                range: Count(2)..Count(5),
+            },
+            Edit::Insert {
+                position: Count(3),
+                items: vec![20],
+            },
+        ]);
+    }
+
+    #[test]
+    fn splice_tree() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+
+        for seed in 0..20 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let mut tree = Tree::<u16>::new();
+            let mut reference_tree = Tree::new();
+            for _ in 0..20 {
+                let end = rng.gen_range(0, tree.len::<Count>().0 + 1);
+                let start = rng.gen_range(0, end + 1);
+                let count = rng.gen_range(0, 50);
+                let new_items = rng.gen_iter().take(count).collect::<Vec<u16>>();
+                let mut replacement = Tree::new();
+                replacement.extend(new_items.iter().cloned());
+
+                tree.splice_tree(&Count(start)..&Count(end), replacement);
+                reference_tree.splice(&Count(start)..&Count(end), new_items);
+                assert_eq!(tree.items(), reference_tree.items());
+                assert_eq!(tree.summary(), reference_tree.summary());
+                assert_fanout(&tree, MIN_CHILDREN, MAX_CHILDREN);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid splice range: RangeOutOfBounds")]
+    fn splice_tree_out_of_bounds() {
+        let mut tree = Tree::new();
+        tree.extend(0..10u16);
+        tree.splice_tree(&Count(8)..&Count(11), Tree::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid splice range: RangeReversed")]
+    fn splice_reversed_range() {
+        let mut tree = Tree::new();
+        tree.extend(0..10u16);
+        tree.splice(&Count(8)..&Count(2), None);
+    }
+
+    #[test]
+    fn summary_is_consistent() {
+        let mut tree = Tree::new();
+        assert!(tree.summary_is_consistent());
+        tree.extend(0..20u16);
+        assert!(tree.summary_is_consistent());
+
+        match Arc::make_mut(&mut tree.0) {
+            &mut Node::Internal {
+                ref mut summary, ..
+            } => summary.sum += 1,
+            &mut Node::Leaf { .. } => unreachable!(),
+        }
+        assert!(!tree.summary_is_consistent());
+    }
+
+    #[test]
+    fn random() {
+        for seed in 0..100 {
+            use self::rand::{Rng, SeedableRng, StdRng};
+            let mut rng = StdRng::from_seed(&[seed]);
+
+            let mut tree = Tree::<u16>::new();
+            let count = rng.gen_range(0, 10);
+            tree.extend(rng.gen_iter::<u16>().take(count));
+
+            for _i in 0..100 {
+                let end = rng.gen_range(0, tree.len::<Count>().0 + 1);
+                let start = rng.gen_range(0, end + 1);
+                let count = rng.gen_range(0, 3);
+                let new_items = rng.gen_iter().take(count).collect::<Vec<u16>>();
+                let mut reference_items = tree.items();
+
+                tree.splice(&Count(start)..&Count(end), new_items.clone());
+                reference_items.splice(start..end, new_items);
+
+                assert_eq!(tree.items(), reference_items);
+                assert!(tree.summary_is_consistent());
+
+                let mut cursor = tree.cursor();
+                let suffix_start = rng.gen_range(0, tree.len::<Count>().0 + 1);
+                let prefix_end = rng.gen_range(0, suffix_start + 1);
+
+                let prefix_items = cursor.slice(&Count(prefix_end), SeekBias::Right).items();
+                assert_eq!(prefix_items, reference_items[0..prefix_end].to_vec());
+
+                // Scan to the start of the suffix if we aren't already there
+                if suffix_start > prefix_end {
+                    for i in prefix_end..suffix_start {
+                        assert_eq!(cursor.item(), reference_items.get(i));
+                        assert_eq!(
+                            cursor.prev_item(),
+                            if i > 0 {
+                                reference_items.get(i - 1)
+                            } else {
+                                None
+                            }
+                        );
+                        assert_eq!(cursor.start::<Count>(), Count(i));
+                        cursor.next();
+                    }
+                }
+
+                let suffix_items = cursor.slice(&tree.len::<Count>(), SeekBias::Right).items();
+                assert_eq!(suffix_items, reference_items[suffix_start..].to_vec());
+
+                let prev_start = rng.gen_range(0, tree.len::<Count>().0 + 1);
+                cursor.seek(&Count(prev_start), SeekBias::Right);
+                for i in (0..prev_start).rev() {
+                    cursor.prev();
+                    assert_eq!(cursor.item(), reference_items.get(i));
+                    assert_eq!(cursor.start::<Count>(), Count(i));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cursor() {
+        // Empty tree
+        let tree = Tree::<u16>::new();
+        let mut cursor = tree.cursor();
+        assert_eq!(cursor.slice(&Sum(0), SeekBias::Right), Tree::new());
+        assert_eq!(cursor.item(), None);
+        assert_eq!(cursor.prev_item(), None);
+        assert_eq!(cursor.start::<Count>(), Count(0));
+        assert_eq!(cursor.start::<Sum>(), Sum(0));
+
+        // Single-element tree
+        let mut tree = Tree::<u16>::new();
+        tree.extend(vec![1]);
+        let mut cursor = tree.cursor();
+        assert_eq!(cursor.slice(&Sum(0), SeekBias::Right), Tree::new());
+        assert_eq!(cursor.item(), Some(&1));
+        assert_eq!(cursor.prev_item(), None);
+        assert_eq!(cursor.start::<Count>(), Count(0));
+        assert_eq!(cursor.start::<Sum>(), Sum(0));
+
+        cursor.next();
+        assert_eq!(cursor.item(), None);
+        assert_eq!(cursor.prev_item(), Some(&1));
+        assert_eq!(cursor.start::<Count>(), Count(1));
+        assert_eq!(cursor.start::<Sum>(), Sum(1));
+
+        cursor.prev();
+        assert_eq!(cursor.item(), Some(&1));
+        assert_eq!(cursor.prev_item(), None);
+        assert_eq!(cursor.start::<Count>(), Count(0));
+        assert_eq!(cursor.start::<Sum>(), Sum(0));
+
+        cursor.reset();
+        assert_eq!(cursor.slice(&Sum(1), SeekBias::Right).items(), [1]);
+        assert_eq!(cursor.item(), None);
+        assert_eq!(cursor.prev_item(), Some(&1));
+        assert_eq!(cursor.start::<Count>(), Count(1));
+        assert_eq!(cursor.start::<Sum>(), Sum(1));
+
+        cursor.seek(&Sum(0), SeekBias::Right);
+        assert_eq!(
+            cursor.slice(&tree.len::<Count>(), SeekBias::Right).items(),
+            [1]
+        );
+        assert_eq!(cursor.item(), None);
+        assert_eq!(cursor.prev_item(), Some(&1));
+        assert_eq!(cursor.start::<Count>(), Count(1));
+        assert_eq!(cursor.start::<Sum>(), Sum(1));
+
+        // Multiple-element tree
+        let mut tree = Tree::new();
+        tree.extend(vec![1, 2, 3, 4, 5, 6]);
+        let mut cursor = tree.cursor();
+
+        assert_eq!(cursor.slice(&Sum(4), SeekBias::Right).items(), [1, 2]);
+        assert_eq!(cursor.item(), Some(&3));
+        assert_eq!(cursor.prev_item(), Some(&2));
+        assert_eq!(cursor.start::<Count>(), Count(2));
+        assert_eq!(cursor.start::<Sum>(), Sum(3));
+
+        cursor.next();
+        assert_eq!(cursor.item(), Some(&4));
+        assert_eq!(cursor.prev_item(), Some(&3));
+        assert_eq!(cursor.start::<Count>(), Count(3));
+        assert_eq!(cursor.start::<Sum>(), Sum(6));
+
+        cursor.next();
+        assert_eq!(cursor.item(), Some(&5));
+        assert_eq!(cursor.prev_item(), Some(&4));
+        assert_eq!(cursor.start::<Count>(), Count(4));
+        assert_eq!(cursor.start::<Sum>(), Sum(10));
+
+        cursor.next();
+        assert_eq!(cursor.item(), Some(&6));
+        assert_eq!(cursor.prev_item(), Some(&5));
+        assert_eq!(cursor.start::<Count>(), Count(5));
+        assert_eq!(cursor.start::<Sum>(), Sum(15));
+
+        cursor.next();
+        cursor.next();
+        assert_eq!(cursor.item(), None);
+        assert_eq!(cursor.prev_item(), Some(&6));
+        assert_eq!(cursor.start::<Count>(), Count(6));
+        assert_eq!(cursor.start::<Sum>(), Sum(21));
+
+        cursor.prev();
+        assert_eq!(cursor.item(), Some(&6));
+        assert_eq!(cursor.prev_item(), Some(&5));
+        assert_eq!(cursor.start::<Count>(), Count(5));
+        assert_eq!(cursor.start::<Sum>(), Sum(15));
+
+        cursor.prev();
+        assert_eq!(cursor.item(), Some(&5));
+        assert_eq!(cursor.prev_item(), Some(&4));
+        assert_eq!(cursor.start::<Count>(), Count(4));
+        assert_eq!(cursor.start::<Sum>(), Sum(10));
+
+        cursor.prev();
+        assert_eq!(cursor.item(), Some(&4));
+        assert_eq!(cursor.prev_item(), Some(&3));
+        assert_eq!(cursor.start::<Count>(), Count(3));
+        assert_eq!(cursor.start::<Sum>(), Sum(6));
+
+        cursor.prev();
+        assert_eq!(cursor.item(), Some(&3));
+        assert_eq!(cursor.prev_item(), Some(&2));
+        assert_eq!(cursor.start::<Count>(), Count(2));
+        assert_eq!(cursor.start::<Sum>(), Sum(3));
+
+        cursor.prev();
+        assert_eq!(cursor.item(), Some(&2));
+        assert_eq!(cursor.prev_item(), Some(&1));
+        assert_eq!(cursor.start::<Count>(), Count(1));
+        assert_eq!(cursor.start::<Sum>(), Sum(1));
+
+        cursor.prev();
+        assert_eq!(cursor.item(), Some(&1));
+        assert_eq!(cursor.prev_item(), None);
+        assert_eq!(cursor.start::<Count>(), Count(0));
+        assert_eq!(cursor.start::<Sum>(), Sum(0));
+
+        cursor.prev();
+        assert_eq!(cursor.item(), None);
+        assert_eq!(cursor.prev_item(), None);
+        assert_eq!(cursor.start::<Count>(), Count(0));
+        assert_eq!(cursor.start::<Sum>(), Sum(0));
+
+        cursor.reset();
+        assert_eq!(
+            cursor.slice(&tree.len::<Count>(), SeekBias::Right).items(),
+            tree.items()
+        );
+        assert_eq!(cursor.item(), None);
+        assert_eq!(cursor.prev_item(), Some(&6));
+        assert_eq!(cursor.start::<Count>(), Count(6));
+        assert_eq!(cursor.start::<Sum>(), Sum(21));
+
+        cursor.seek(&Count(3), SeekBias::Right);
+        assert_eq!(
+            cursor.slice(&tree.len::<Count>(), SeekBias::Right).items(),
+            [4, 5, 6]
+        );
+        assert_eq!(cursor.item(), None);
+        assert_eq!(cursor.prev_item(), Some(&6));
+        assert_eq!(cursor.start::<Count>(), Count(6));
+        assert_eq!(cursor.start::<Sum>(), Sum(21));
+
+        cursor.seek(&Count(4), SeekBias::Right);
+        assert_eq!(
+            cursor.start_many(|summary| (Count::from_summary(summary), Sum::from_summary(summary))),
+            (cursor.start::<Count>(), cursor.start::<Sum>())
+        );
+        assert_eq!(
+            cursor.start_many(|summary| (Count::from_summary(summary), Sum::from_summary(summary))),
+            (Count(4), Sum(10))
+        );
+
+        // Moving back several items at once
+        cursor.seek(&Count(5), SeekBias::Right);
+        cursor.prev_n(&Count(3));
+        assert_eq!(cursor.item(), Some(&3));
+        assert_eq!(cursor.prev_item(), Some(&2));
+        assert_eq!(cursor.start::<Count>(), Count(2));
+        cursor.prev_n(&Count(10));
+        assert_eq!(cursor.item(), Some(&1));
+        assert_eq!(cursor.prev_item(), None);
+        assert_eq!(cursor.start::<Count>(), Count(0));
+
+        // Seeking can bias left or right
+        cursor.seek(&Sum(1), SeekBias::Left);
+        assert_eq!(cursor.item(), Some(&1));
+        cursor.seek(&Sum(1), SeekBias::Right);
+        assert_eq!(cursor.item(), Some(&2));
+
+        // Slicing without resetting starts from where the cursor is parked at.
+        cursor.seek(&Sum(1), SeekBias::Right);
+        assert_eq!(cursor.slice(&Sum(6), SeekBias::Right).items(), vec![2, 3]);
+        assert_eq!(cursor.slice(&Sum(21), SeekBias::Left).items(), vec![4, 5]);
+        assert_eq!(cursor.slice(&Sum(21), SeekBias::Right).items(), vec![6]);
+    }
+}
@@ -0,0 +1,238 @@
+use super::{Dimension, Item, MaxSummary};
+use std::fmt;
+use std::ops::{Add, AddAssign};
+
+/// Wraps an opaque value whose summary only tracks the number of items. Trees of `Counted`
+/// items are measured with the `ItemCount` dimension.
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Counted<T>(pub T);
+
+/// Wraps an integer whose summary tracks both the number of items and their sum. Wrapping
+/// keeps these impls from conflicting with `Item` impls for the integer types themselves.
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Scalar<T>(pub T);
+
+/// Wraps an ordered value whose summary tracks the greatest value among the items. This turns a
+/// tree into a priority queue supporting `Tree::peek_max` and `Tree::pop_max`, which can be
+/// cloned in O(1) and merged with `Tree::append`.
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Prioritized<T>(pub T);
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PrioritizedSummary<T> {
+    pub count: usize,
+    pub max: Option<T>,
+}
+
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct ScalarSummary {
+    pub count: usize,
+    pub sum: i128,
+}
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Count(pub usize);
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ItemCount(pub usize);
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Sum(pub i128);
+
+impl<T: Clone + Eq + fmt::Debug> Item for Counted<T> {
+    type Summary = usize;
+
+    fn summarize(&self) -> Self::Summary {
+        1
+    }
+}
+
+impl<T: Ord + Clone + fmt::Debug> Item for Prioritized<T> {
+    type Summary = PrioritizedSummary<T>;
+
+    fn summarize(&self) -> Self::Summary {
+        PrioritizedSummary {
+            count: 1,
+            max: Some(self.0.clone()),
+        }
+    }
+}
+
+impl<T> Default for PrioritizedSummary<T> {
+    fn default() -> Self {
+        PrioritizedSummary {
+            count: 0,
+            max: None,
+        }
+    }
+}
+
+impl<'a, T: Ord + Clone> AddAssign<&'a Self> for PrioritizedSummary<T> {
+    fn add_assign(&mut self, other: &Self) {
+        self.count += other.count;
+        if other.max > self.max {
+            self.max = other.max.clone();
+        }
+    }
+}
+
+impl<T: Ord + Clone> MaxSummary for PrioritizedSummary<T> {
+    type Key = Option<T>;
+
+    fn max_key(&self) -> Self::Key {
+        self.max.clone()
+    }
+}
+
+impl Dimension for ItemCount {
+    type Summary = usize;
+
+    fn from_summary(summary: &Self::Summary) -> Self {
+        ItemCount(*summary)
+    }
+}
+
+impl<'a> Add<&'a Self> for ItemCount {
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self {
+        ItemCount(self.0 + other.0)
+    }
+}
+
+macro_rules! impl_scalar_item {
+    ($($t:ty),*) => {
+        $(
+            impl Item for Scalar<$t> {
+                type Summary = ScalarSummary;
+
+                fn summarize(&self) -> Self::Summary {
+                    ScalarSummary {
+                        count: 1,
+                        sum: self.0 as i128,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar_item!(u8, u16, u32, u64, i32, i64, usize);
+
+impl<'a> AddAssign<&'a Self> for ScalarSummary {
+    fn add_assign(&mut self, other: &Self) {
+        self.count += other.count;
+        self.sum += other.sum;
+    }
+}
+
+impl Dimension for Count {
+    type Summary = ScalarSummary;
+
+    fn from_summary(summary: &Self::Summary) -> Self {
+        Count(summary.count)
+    }
+}
+
+impl<'a> Add<&'a Self> for Count {
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self {
+        Count(self.0 + other.0)
+    }
+}
+
+impl Dimension for Sum {
+    type Summary = ScalarSummary;
+
+    fn from_summary(summary: &Self::Summary) -> Self {
+        Sum(summary.sum)
+    }
+}
+
+impl<'a> Add<&'a Self> for Sum {
+    type Output = Self;
+
+    fn add(self, other: &Self) -> Self {
+        Sum(self.0 + other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use super::super::{SeekBias, Tree};
+    use super::*;
+
+    #[test]
+    fn test_counted() {
+        let mut tree = Tree::new();
+        tree.extend(vec!["a", "b", "c", "d"].into_iter().map(Counted));
+        assert_eq!(tree.len::<ItemCount>(), ItemCount(4));
+
+        let mut cursor = tree.cursor();
+        cursor.seek(&ItemCount(2), SeekBias::Right);
+        assert_eq!(cursor.item(), Some(&Counted("c")));
+    }
+
+    #[test]
+    fn test_prioritized() {
+        use self::rand::{Rng, SeedableRng, StdRng};
+        use std::collections::BinaryHeap;
+
+        for seed in 0..10 {
+            let mut rng = StdRng::from_seed(&[seed]);
+            let mut tree = Tree::<Prioritized<u32>>::new();
+            let mut heap = BinaryHeap::new();
+            for _ in 0..500 {
+                if rng.gen_weighted_bool(3) {
+                    assert_eq!(tree.pop_max().map(|item| item.0), heap.pop());
+                } else {
+                    let value = rng.gen_range(0, 100);
+                    tree.push(Prioritized(value));
+                    heap.push(value);
+                }
+                assert_eq!(tree.peek_max().map(|item| item.0), heap.peek().cloned());
+                assert_eq!(tree.summary().count, heap.len());
+            }
+
+            // Popping from a merged queue leaves earlier clones untouched.
+            let snapshot = tree.clone();
+            let snapshot_count = snapshot.summary().count;
+            let snapshot_max = snapshot.peek_max().cloned();
+            let mut other_tree = Tree::new();
+            other_tree.extend((0..50).map(Prioritized));
+            tree.append(&mut other_tree);
+            heap.extend(0..50);
+            while let Some(value) = heap.pop() {
+                assert_eq!(tree.pop_max(), Some(Prioritized(value)));
+            }
+            assert_eq!(tree.pop_max(), None);
+            assert_eq!(snapshot.summary().count, snapshot_count);
+            assert_eq!(snapshot.peek_max().cloned(), snapshot_max);
+        }
+    }
+
+    #[test]
+    fn test_scalars() {
+        let mut tree = Tree::new();
+        tree.extend((1..11).map(Scalar::<u8>));
+        assert_eq!(tree.len::<Count>(), Count(10));
+        assert_eq!(tree.len::<Sum>(), Sum(55));
+
+        let mut cursor = tree.cursor();
+        cursor.seek(&Sum(10), SeekBias::Right);
+        assert_eq!(cursor.item(), Some(&Scalar(5)));
+        assert_eq!(cursor.start::<Count>(), Count(4));
+
+        let mut tree = Tree::new();
+        tree.extend(vec![Scalar(u64::max_value()), Scalar(u64::max_value())]);
+        assert_eq!(tree.len::<Sum>(), Sum(2 * u64::max_value() as i128));
+
+        let mut tree = Tree::new();
+        tree.extend(vec![Scalar(-5i64), Scalar(3), Scalar(-1)]);
+        assert_eq!(tree.len::<Count>(), Count(3));
+        assert_eq!(tree.len::<Sum>(), Sum(-3));
+    }
+}